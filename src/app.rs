@@ -1,55 +1,114 @@
-use std::{fs::{self, remove_file}, io};
+use std::{fs::{self, remove_file}, io, cmp::Reverse, collections::{HashMap, HashSet}, path::{Path, PathBuf}, time::{Duration, UNIX_EPOCH}};
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+use chrono::NaiveDate;
+use age::DecryptError;
 
 use crate::Args;
+use crate::config::{Config, Security, Sort};
+use crate::locale::{Locale, Message};
 
-mod encryption;
+pub mod autosave;
+pub mod encryption;
+pub mod entry;
 pub mod file;
+pub mod search;
+pub mod preview;
+pub mod summary;
+pub mod stats;
+mod index;
 use encryption::Encryption;
+use entry::{Entry, Times};
 use file::Directory;
+use file::date::{self, DateFilter};
+use file::trash::Trash;
+use search::{Matcher, SearchHit, SearchOptions};
+use stats::{EntryStats, Series, StatsCache, Writing};
+use index::Index;
+use preview::Previewer;
+use autosave::Autosaver;
+use summary::{fields, is_tag, split_front_matter, Summary};
 
 pub struct App {
     encryption: Option<Encryption>,
     directory: Directory,
     journals_to_add: Vec<String>,
+    contents: HashMap<String, String>,
+    times: HashMap<String, Times>,
+    summaries: HashMap<String, Summary>,
+    index: Option<Index>,
+    stats: Option<StatsCache>,
+    sort: Sort,
+    /// Only list entries with this tag, and the names of those entries once they're known
+    tag: Option<String>,
+    tagged: Option<HashSet<String>>,
+    /// Only list entries written on this day in earlier years
+    on_this_day: Option<HashSet<String>>,
+    locale: Locale,
+    config: Config,
 }
 
 #[derive(Debug)]
 pub enum AppError {
     IncorrectPassword,
-    NoPassphrase,
+    /// An entry was encrypted with a higher scrypt work factor than `security.max_work_factor`
+    ExcessiveWork(u8),
 }
 
-pub fn encryption_from_option_passphrase(passphrase: Option<String>) -> Option<Encryption> {
-    match passphrase {
-        Some(passphrase) => Some(Encryption::new(passphrase)),
-        None => None,
-    }
+pub fn encryption_from_option_passphrase(passphrase: Option<String>, security: &Security) -> io::Result<Option<Encryption>> {
+    passphrase.map(|passphrase| encryption_from_passphrase(passphrase, security)).transpose()
+}
+
+pub fn encryption_from_passphrase(passphrase: String, security: &Security) -> io::Result<Encryption> {
+    Ok(Encryption::new(passphrase)
+        .with_work_factor(security.work_factor)?
+        .with_max_work_factor(security.max_work_factor))
+}
+
+/// Size and modification time of the file at `path`, which change whenever it's rewritten.
+fn file_stamp(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!("{}.{}", metadata.len(), modified.as_nanos()))
+}
+
+#[inline]
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().to_string()
 }
 
 impl App {
-    pub fn new(args:Args) -> io::Result<Self> {
+    pub fn new(args:Args, config: Config) -> io::Result<Self> {
+        let directory = Directory::new(config.path.clone()).unwrap();
         let mut app = App {
             journals_to_add: args.add,
-            encryption: encryption_from_option_passphrase(args.passphrase),
-            directory: Directory::new(args.path).unwrap(),
+            encryption: encryption_from_option_passphrase(args.passphrase, &config.security)?,
+            directory,
+            contents: HashMap::new(),
+            times: HashMap::new(),
+            summaries: HashMap::new(),
+            index: None,
+            stats: None,
+            sort: args.sort.unwrap_or(config.sort),
+            tag: args.tag,
+            tagged: None,
+            on_this_day: None,
+            locale: Locale::detect(config.locale),
+            config,
         };
+        app.purge_trash()?;
         app.add_journals()?;
         Ok(app)
     }
 
-    pub fn change_password(&mut self, new_password: String) -> io::Result<()>{
-        let new_encryption = Encryption::new(new_password);
-        if let Some(encryption) = &self.encryption {
-            self.directory.read(|path| {
-                let string = fs::read(&path).unwrap();
-                let decrypted = encryption.decrypt(string).unwrap();
-                if let Ok(encrypted) = new_encryption.encrypt(decrypted) {
-                    fs::write(&path, encrypted)?;
-                }
-                Ok(())
-            })?;
-        }
-        Ok(())
+    #[inline]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    #[inline]
+    pub fn locale(&self) -> Locale {
+        self.locale
     }
 
     pub fn len(&self) -> usize {
@@ -61,90 +120,660 @@ impl App {
     }
 
     pub fn nth_content(&mut self, n: usize) -> String {
-        if let Some(encryption) = &self.encryption {
-            let path = self.directory.nth_path(n).unwrap();
-            let encrypted = fs::read(path).unwrap();
-            let decrypted =encryption.decrypt(encrypted);
-            return decrypted.unwrap()
+        match self.entries().get(n) {
+            Some(name) => self.content(name).unwrap_or_default(),
+            None => String::new(),
         }
-        String::new()
     }
 
     pub fn add_journals(&mut self) -> io::Result<()>{
-        if self.test_passphrase().is_ok() {
-            for journal in &self.journals_to_add {
+        if self.encryption.is_some() && self.test_passphrase().is_ok() {
+            for journal in std::mem::take(&mut self.journals_to_add) {
                 self.add_journal(journal)?;
             }
         }
         Ok(())
     }
 
-    pub fn add_journal<S: AsRef<str>>(&self, journal: S) -> io::Result<()>{
-        if let Some(encryption) = &self.encryption {
-            let encrypted = encryption.encrypt(journal).unwrap();
-            let path = self.directory.new_path().unwrap();
-            fs::write(path, encrypted)?;
+    /// Check the configured fields in the front-matter of `text`, like `mood: 7`, returning why
+    /// one can't be saved. Values left as they are in `previous`, the saved text being edited,
+    /// pass, so entries written before a field was configured can still be edited.
+    pub fn check_fields(&self, text: &str, previous: &str) -> Result<(), String> {
+        let (front_matter, _) = split_front_matter(text);
+        let (previous, _) = split_front_matter(previous);
+        for (key, value) in fields(front_matter) {
+            let Some(field) = self.config.fields.iter().find(|field| field.name == key) else {
+                continue
+            };
+            if !value.is_empty() && !fields(previous).any(|pair| pair == (key, value)) {
+                field.parse(value, self.locale).map_err(|error| format!("{}: {}", key, error))?;
+            }
         }
         Ok(())
     }
 
-    pub fn has_passphrase(&self) -> bool {
-        self.encryption.is_some()
+    /// Encrypt `journal` into a new entry. Returns the name of the entry.
+    pub fn add_journal<S: AsRef<str>>(&mut self, journal: S) -> io::Result<Option<String>>{
+        self.check_fields(journal.as_ref(), "").map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        if let Some(encryption) = &self.encryption {
+            let entry = Entry::new(journal.as_ref());
+            let encrypted = encryption.encrypt(entry.serialize()).map_err(io::Error::other)?;
+            let path = self.directory.new_path().unwrap();
+            fs::write(&path, encrypted)?;
+            let name = file_name(&path);
+            self.on_write(name.clone(), &entry)?;
+            return Ok(Some(name))
+        }
+        Ok(None)
     }
 
     pub fn no_passphrase(&self) -> bool {
         self.encryption.is_none()
     }
 
-    pub fn set_passphrase(&mut self, passphrase: String) {
-        self.encryption = Some(Encryption::new(passphrase))
+    pub fn set_passphrase(&mut self, passphrase: String) -> io::Result<()> {
+        self.encryption = Some(encryption_from_passphrase(passphrase, &self.config.security)?);
+        self.contents.clear();
+        self.times.clear();
+        self.summaries.clear();
+        self.tagged = None;
+        self.index = None;
+        self.stats = None;
+        Ok(())
     }
 
     pub fn test_passphrase(&mut self) -> Result<(), AppError> {
-        if let Some(first_path) = self.directory.nth_path(0) {
+        let first = self.directory.all_entries().unwrap_or_default().into_iter().next();
+        if let Some(first_path) = first.map(|name| self.directory.entry_path(name)) {
             let decrypted = match (fs::read(&first_path), &self.encryption) {
                 (Ok(encrypted), Some(encryption)) => {
                     encryption.decrypt(encrypted)
                 },
                 _ => return Ok(()),
             };
-            if decrypted.is_err() {
-                return Err(AppError::IncorrectPassword);
+            match decrypted {
+                Err(DecryptError::ExcessiveWork { required, .. }) => return Err(AppError::ExcessiveWork(required)),
+                Err(_) => return Err(AppError::IncorrectPassword),
+                Ok(_) => {},
+            }
+        }
+        Ok(())
+    }
+
+    /// Overwrite the entry `name` with the text `journal`, keeping its previous version as a
+    /// revision.
+    pub fn edit(&mut self, name: &str, journal: String) -> io::Result<()> {
+        let entry = self.entry(name)?;
+        self.check_fields(&journal, &entry.to_text()).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        self.write_edit(name, entry, &journal)
+    }
+
+    fn write_edit(&mut self, name: &str, mut entry: Entry, journal: &str) -> io::Result<()> {
+        entry.edit(journal);
+        self.write_entry(name, &entry)
+    }
+
+    /// Overwrite the entry `name` with `entry`, keeping the version it replaces as a revision.
+    fn write_entry(&mut self, name: &str, entry: &Entry) -> io::Result<()> {
+        if let Some(encryption) = &self.encryption {
+            let path = self.directory.entry_path(name);
+            let encrypted = encryption.encrypt(entry.serialize()).map_err(io::Error::other)?;
+            if self.config.history_keep > 0 {
+                let history = self.directory.history();
+                history.insert(&path)?;
+                history.prune(name, self.config.history_keep)?;
+            }
+            fs::write(&path, encrypted)?;
+            self.on_write(name.to_string(), entry)?;
+        }
+        Ok(())
+    }
+
+    /// Revisions of the entry `name`, newest first.
+    #[inline]
+    pub fn revisions(&self, name: &str) -> io::Result<Vec<String>> {
+        self.directory.history().revisions(name)
+    }
+
+    pub fn revision_content(&self, name: &str, revision: &str) -> io::Result<String> {
+        let path = self.directory.history().revision_path(name, revision);
+        Ok(self.decrypt_entry(name, &path)?.to_text())
+    }
+
+    /// Make `revision` the current version of the entry `name`. The version it replaces becomes a
+    /// revision itself. Its fields aren't checked again, since they were saved once already.
+    pub fn restore_revision(&mut self, name: &str, revision: &str) -> io::Result<()> {
+        let content = self.revision_content(name, revision)?;
+        let entry = self.entry(name)?;
+        self.write_edit(name, entry, &content)
+    }
+
+    /// The text of the entry `name`, as it's edited.
+    pub fn content(&self, name: &str) -> io::Result<String> {
+        Ok(self.entry(name)?.to_text())
+    }
+
+    pub fn entry(&self, name: &str) -> io::Result<Entry> {
+        self.decrypt_entry(name, &self.directory.entry_path(name))
+    }
+
+    fn decrypt_entry(&self, name: &str, path: &Path) -> io::Result<Entry> {
+        let plaintext = self.decrypt_file(path)?;
+        Entry::deserialize(name, &plaintext, self.locale)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Rewrite entries from before envelopes in the current format, keeping each original as a
+    /// revision even when history is off. Returns their names.
+    pub fn migrate(&mut self) -> io::Result<Vec<String>> {
+        let Some(encryption) = &self.encryption else {
+            return Ok(vec![])
+        };
+        let mut migrated = vec![];
+        for name in self.directory.all_entries()? {
+            let path = self.directory.entry_path(&name);
+            let plaintext = encryption.decrypt(fs::read(&path)?)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", name, error)))?;
+            if Entry::is_legacy(&plaintext) {
+                let entry = Entry::deserialize(&name, &plaintext, self.locale)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+                let encrypted = encryption.encrypt(entry.serialize()).map_err(io::Error::other)?;
+                self.directory.history().insert(&path)?;
+                fs::write(&path, encrypted)?;
+                migrated.push(name);
+            }
+        }
+        Ok(migrated)
+    }
+
+    fn decrypt_file(&self, path: &Path) -> io::Result<String> {
+        let Some(encryption) = &self.encryption else {
+            return Ok(String::new())
+        };
+        let encrypted = fs::read(path)?;
+        encryption.decrypt(encrypted)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))
+    }
+
+    /// Drafts left behind by editors that weren't closed, oldest first.
+    #[inline]
+    pub fn drafts(&self) -> io::Result<Vec<String>> {
+        self.directory.drafts().names()
+    }
+
+    pub fn draft_content(&self, draft: &str) -> io::Result<String> {
+        self.decrypt_file(&self.directory.drafts().draft_path(draft))
+    }
+
+    #[inline]
+    pub fn discard_draft(&self, draft: &str) -> io::Result<()> {
+        self.directory.drafts().remove(draft)
+    }
+
+    /// Move the entry `name` to the trash. It's shredded instead with `shred` or the
+    /// `security.shred` policy, and removed right away when `trash_days` is 0, along with its
+    /// revisions. A draft of an edit to it is discarded either way.
+    pub fn delete(&mut self, name: &str, shred: bool) -> io::Result<()> {
+        let path = self.directory.entry_path(name);
+        if shred || self.config.security.shred {
+            file::shred(&path)?;
+            self.directory.history().remove(name)?;
+        } else if self.config.trash_days == 0 {
+            remove_file(&path)?;
+            self.directory.history().remove(name)?;
+        } else {
+            self.directory.trash().insert(&path)?;
+        }
+        self.directory.drafts().remove(name)?;
+        self.contents.remove(name);
+        self.times.remove(name);
+        self.summaries.remove(name);
+        self.tagged = None;
+        self.update_index(|index| index.remove(name))?;
+        Ok(())
+    }
+
+    /// Move the entry `name` out of the trash.
+    pub fn restore(&mut self, name: &str) -> io::Result<()> {
+        let path = self.directory.entry_path(name);
+        if path.exists() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, self.locale.format(Message::EntryExists, &[&name])))
+        }
+        self.directory.trash().restore(name, &path)?;
+        self.tagged = None;
+        if self.load_index().is_some() {
+            if let Some((name, content)) = self.contents_of(vec![name.to_string()]).pop() {
+                self.update_index(|index| index.insert(&name, &content))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn trash(&self) -> Trash {
+        self.directory.trash()
+    }
+
+    /// Shred entries that have been in the trash for longer than `trash_days`, with their
+    /// revisions.
+    pub fn purge_trash(&self) -> io::Result<usize> {
+        let max_age = Duration::from_secs(self.config.trash_days * 24 * 60 * 60);
+        let removed = self.directory.trash().purge(max_age)?;
+        for name in &removed {
+            self.directory.history().remove(name)?;
+        }
+        Ok(removed.len())
+    }
+
+    /// Shred every entry in the trash, with their revisions.
+    pub fn empty_trash(&self) -> io::Result<usize> {
+        let removed = self.directory.trash().empty()?;
+        for name in &removed {
+            self.directory.history().remove(name)?;
+        }
+        Ok(removed.len())
+    }
+
+    /// The decrypted content of the entry `name`, if it's been decrypted already.
+    #[inline]
+    pub fn cached_content(&self, name: &str) -> Option<&str> {
+        self.contents.get(name).map(String::as_str)
+    }
+
+    /// Keep an entry decrypted elsewhere, like by a `Previewer`.
+    pub fn cache_entry(&mut self, name: String, entry: &Entry) {
+        self.times.insert(name.clone(), entry.times());
+        self.contents.insert(name, entry.to_text());
+    }
+
+    /// When the entry `name` was written and last edited, once it's been decrypted.
+    #[inline]
+    pub fn times(&self, name: &str) -> Option<Times> {
+        self.times.get(name).copied()
+    }
+
+    /// The summary of the entry `name`, once its content has been decrypted.
+    pub fn summary(&mut self, name: &str) -> Option<&Summary> {
+        if !self.summaries.contains_key(name) {
+            let summary = Summary::new(self.contents.get(name)?);
+            self.summaries.insert(name.to_string(), summary);
+        }
+        self.summaries.get(name)
+    }
+
+    /// How many entries were written on each day, and how many words they have as far as they've
+    /// been decrypted.
+    pub fn activity(&mut self) -> HashMap<NaiveDate, (usize, usize)> {
+        let mut activity: HashMap<NaiveDate, (usize, usize)> = HashMap::new();
+        let cache = self.stats_cache();
+        for name in self.directory.all_entries().unwrap_or_default() {
+            let Some(created) = date::parse(&name) else {
+                continue
+            };
+            // Entries that aren't decrypted yet count as long as they were when last cached
+            let words = self.summary(&name).map(|summary| summary.words)
+                .or_else(|| cache.entries.get(&name).map(|stats| stats.words))
+                .unwrap_or(0);
+            let day = activity.entry(created.date_naive()).or_default();
+            day.0 += 1;
+            day.1 += words;
+        }
+        self.stats = Some(cache);
+        activity
+    }
+
+    /// Numeric fields of the entries in the current view over time.
+    pub fn series(&mut self) -> io::Result<Series> {
+        let points = self.entry_stats()?.into_iter()
+            .map(|stats| (stats.created, stats.values))
+            .collect();
+        let configured: Vec<String> = self.config.fields.iter().map(|field| field.name.clone()).collect();
+        Ok(Series::new(&configured, points))
+    }
+
+    /// How much and when the entries in the current view were written.
+    pub fn writing(&mut self) -> io::Result<Writing> {
+        let stats = self.entry_stats()?;
+        Ok(Writing::new(&stats, self.config.calendar, date::current().date_naive()))
+    }
+
+    /// The stats cache, taken out of `stats` or read from disk the first time.
+    fn stats_cache(&mut self) -> StatsCache {
+        match self.stats.take() {
+            Some(cache) => cache,
+            None => self.decrypt_file(&self.directory.stats_path())
+                .map(|decrypted| StatsCache::deserialize(&decrypted))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Stats of the entries in the current view. They come from the stats cache for entries that
+    /// haven't changed since it was saved; the others are decrypted, and the cache updated.
+    fn entry_stats(&mut self) -> io::Result<Vec<EntryStats>> {
+        if self.encryption.is_none() {
+            return Ok(vec![])
+        }
+        let mut cache = self.stats_cache();
+        let entries = self.entries();
+        let stamps: HashMap<String, String> = entries.iter()
+            .filter_map(|name| Some((name.clone(), file_stamp(&self.directory.entry_path(name))?)))
+            .collect();
+        let stale: Vec<String> = stamps.iter()
+            .filter(|(name, stamp)| cache.entries.get(*name).is_none_or(|stats| stats.stamp != **stamp))
+            .map(|(name, _)| name.clone())
+            .collect();
+        if !stale.is_empty() {
+            for (name, _) in self.contents_of(stale) {
+                let Some(times) = self.times(&name) else {
+                    continue
+                };
+                let Some(summary) = self.summary(&name) else {
+                    continue
+                };
+                let stats = EntryStats {
+                    stamp: stamps[&name].clone(),
+                    created: times.created,
+                    words: summary.words,
+                    values: summary.values.clone(),
+                };
+                cache.entries.insert(name, stats);
+            }
+            let all = self.directory.all_entries()?;
+            cache.entries.retain(|name, _| all.contains(name));
+            if let Some(encryption) = &self.encryption {
+                let encrypted = encryption.encrypt(cache.serialize()).map_err(io::Error::other)?;
+                fs::write(self.directory.stats_path(), encrypted)?;
             }
         }
+        let stats = entries.iter()
+            .filter_map(|name| cache.entries.get(name).cloned())
+            .collect();
+        self.stats = Some(cache);
+        Ok(stats)
+    }
+
+    #[inline]
+    pub fn entry_path(&self, name: &str) -> PathBuf {
+        self.directory.entry_path(name)
+    }
+
+    /// A background decryptor for previews, once there's a passphrase.
+    pub fn previewer(&self) -> Option<Previewer> {
+        self.encryption.clone().map(|encryption| Previewer::new(encryption, self.locale))
+    }
+
+    /// A background writer of encrypted drafts, once there's a passphrase.
+    pub fn autosaver(&self) -> Option<Autosaver> {
+        self.encryption.clone().map(|encryption| Autosaver::new(encryption, self.directory.drafts()))
+    }
+
+    /// Keep the caches and the search index in sync with a newly written entry.
+    fn on_write(&mut self, name: String, entry: &Entry) -> io::Result<()> {
+        let content = entry.to_text();
+        self.update_index(|index| index.insert(&name, &content))?;
+        self.summaries.remove(&name);
+        self.tagged = None;
+        self.cache_entry(name, entry);
         Ok(())
     }
 
-    pub fn edit_nth(&mut self, n: usize, journal: String) -> io::Result<()>{
-        if let (Some(path), Some(encryption)) = (self.directory.nth_path(n), &self.encryption){
-            fs::write(path, encryption.encrypt(journal).unwrap())?;
+    /// Load the encrypted search index from the directory. `None` when there's no index yet, or it
+    /// can't be decrypted.
+    fn load_index(&mut self) -> Option<&mut Index> {
+        if self.index.is_none() {
+            let encryption = self.encryption.as_ref()?;
+            let encrypted = fs::read(self.directory.index_path()).ok()?;
+            let decrypted = encryption.decrypt(encrypted).ok()?;
+            self.index = Some(Index::deserialize(&decrypted));
+        }
+        self.index.as_mut()
+    }
+
+    fn save_index(&self) -> io::Result<()> {
+        if let (Some(index), Some(encryption)) = (&self.index, &self.encryption) {
+            let encrypted = encryption.encrypt(index.serialize()).map_err(io::Error::other)?;
+            fs::write(self.directory.index_path(), encrypted)?;
         }
         Ok(())
     }
 
-    pub fn delete_nth(&mut self, n: usize) -> io::Result<()>{
-        if let Some(path) = self.directory.nth_path(n) {
-            remove_file(path)?;
+    /// Apply `update` to the search index, if the directory has one.
+    fn update_index<F: FnOnce(&mut Index)>(&mut self, update: F) -> io::Result<()> {
+        if let Some(index) = self.load_index() {
+            update(index);
+            self.save_index()?;
         }
         Ok(())
     }
 
+    /// The search index, built from scratch when the directory doesn't have one yet.
+    fn index(&mut self) -> io::Result<&Index> {
+        if self.load_index().is_none() {
+            self.reindex()?;
+        }
+        Ok(self.index.get_or_insert_with(Index::default))
+    }
+
+    /// Rebuild the search index from every entry. Returns the number of indexed entries.
+    pub fn reindex(&mut self) -> io::Result<usize> {
+        if self.encryption.is_none() {
+            return Ok(0)
+        }
+        let entries = self.directory.all_entries()?;
+        let mut index = Index::default();
+        let contents = self.contents_of(entries);
+        for (name, content) in &contents {
+            index.insert(name, content);
+        }
+        self.index = Some(index);
+        self.save_index()?;
+        Ok(contents.len())
+    }
+
+    /// Decrypted content of the `entries`. Decryption runs in parallel and is cached in memory.
+    fn contents_of(&mut self, entries: Vec<String>) -> Vec<(String, String)> {
+        if let Some(encryption) = &self.encryption {
+            let locale = self.locale;
+            let missing: Vec<&String> = entries.iter()
+                .filter(|name| !self.contents.contains_key(*name))
+                .collect();
+            let decrypted: Vec<(String, Entry)> = missing.into_par_iter()
+                .filter_map(|name| {
+                    let encrypted = fs::read(self.directory.entry_path(name)).ok()?;
+                    let decrypted = encryption.decrypt(encrypted).ok()?;
+                    Some((name.clone(), Entry::deserialize(name, &decrypted, locale).ok()?))
+                })
+                .collect();
+            for (name, entry) in decrypted {
+                self.cache_entry(name, &entry);
+            }
+        }
+        entries.into_iter()
+            .filter_map(|name| {
+                let content = self.contents.get(&name)?.clone();
+                Some((name, content))
+            })
+            .collect()
+    }
+
+    /// Search the (filtered) entries. Plain text searches only decrypt the entries the index
+    /// says may match.
+    pub fn search(&mut self, pattern: &str, options: &SearchOptions) -> Result<Vec<SearchHit>, regex::Error> {
+        let matcher = Matcher::new(pattern, options)?;
+        let mut entries = self.entries();
+        if !options.regex && !options.fuzzy && !index::tokenize(pattern).is_empty() {
+            if let Ok(index) = self.index() {
+                if let Some(candidates) = index.candidates(pattern, options.word) {
+                    entries.retain(|name| candidates.contains(name));
+                }
+            }
+        }
+        Ok(self.contents_of(entries).into_par_iter()
+            .filter_map(|(name, content)| SearchHit::new(name, &content, &matcher, options.context))
+            .collect())
+    }
+
+    /// Narrow `entries` down to the ones containing every word of `query` according to the search
+    /// index, or show everything again on `None`.
+    pub fn set_query(&mut self, query: Option<&str>) -> io::Result<()> {
+        self.directory.set_matches(None);
+        if let Some(query) = query {
+            if self.encryption.is_some() {
+                let matches = self.index()?.candidates(query, false);
+                self.directory.set_matches(matches);
+            }
+        }
+        Ok(())
+    }
+
+    /// Names of the (filtered) entries, newest first by `sort`. Entries that haven't been decrypted
+    /// yet sort by when they were written, and so do entries on this day, to list them by year.
     pub fn entries(&mut self) -> Vec<String> {
-        match self.directory.entries() {
-            Ok(entry) => entry,
-            _ => vec![],
+        let mut entries = self.directory.entries().unwrap_or_default();
+        if let Some(tagged) = self.tagged_entries() {
+            entries.retain(|name| tagged.contains(name));
+        }
+        if let Some(on_this_day) = &self.on_this_day {
+            entries.retain(|name| on_this_day.contains(name));
+        }
+        if self.sort == Sort::Modified && self.on_this_day.is_none() {
+            entries.sort_by_cached_key(|name| {
+                let modified = self.times.get(name).map(|times| times.modified);
+                Reverse(modified.or_else(|| date::parse(name).map(|date| date.fixed_offset())))
+            });
+        }
+        entries
+    }
+
+    #[inline]
+    pub fn sort(&self) -> Sort {
+        self.sort
+    }
+
+    #[inline]
+    pub fn set_sort(&mut self, sort: Sort) {
+        self.sort = sort
+    }
+
+    #[inline]
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Only list entries tagged `tag`, or every entry again on `None`.
+    #[inline]
+    pub fn set_tag(&mut self, tag: Option<String>) {
+        self.tag = tag;
+        self.tagged = None;
+    }
+
+    #[inline]
+    pub fn on_this_day(&self) -> bool {
+        self.on_this_day.is_some()
+    }
+
+    /// Only list entries written on today's day of the year in earlier years, or every entry again.
+    pub fn set_on_this_day(&mut self, on: bool) -> io::Result<()> {
+        self.on_this_day = None;
+        if on {
+            let today = date::current().date_naive();
+            let entries = self.directory.on_this_day(today, self.config.calendar)?;
+            self.on_this_day = Some(entries.into_iter().collect());
         }
+        Ok(())
     }
 
-    pub fn read(&self) -> io::Result<()> {
+    /// A random entry of the list, for rediscovering old ones.
+    pub fn random_entry(&mut self) -> Option<String> {
+        self.entries().choose(&mut rand::thread_rng()).cloned()
+    }
+
+    /// Names of the entries with the listed tag, or `None` when the list isn't filtered by tag.
+    /// Finding them decrypts every entry the first time.
+    fn tagged_entries(&mut self) -> Option<&HashSet<String>> {
+        let tag = self.tag.clone()?;
+        if self.tagged.is_none() {
+            let entries = self.directory.all_entries().unwrap_or_default();
+            let tagged = self.contents_of(entries).into_iter()
+                .map(|(name, _)| name)
+                .filter(|name| self.summary(name).is_some_and(|summary| summary.tags.contains(&tag)))
+                .collect();
+            self.tagged = Some(tagged);
+        }
+        self.tagged.as_ref()
+    }
+
+    /// Tags of the entries in the current view, ignoring the tag filter, with how many entries
+    /// have each. Most used first.
+    pub fn tags(&mut self) -> Vec<(String, usize)> {
+        let entries = self.directory.entries().unwrap_or_default();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for (name, _) in self.contents_of(entries) {
+            for tag in self.summary(&name).map(|summary| summary.tags.clone()).unwrap_or_default() {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+        let mut tags: Vec<(String, usize)> = counts.into_iter().collect();
+        tags.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        tags
+    }
+
+    /// Rename the tag `old` to `new` in every entry that has it, merging the two when entries are
+    /// already tagged `new`. Each rewritten entry keeps its previous version as a revision, and
+    /// isn't counted as edited. Returns how many entries were rewritten, or fails when `new` isn't
+    /// a tag.
+    pub fn rename_tag(&mut self, old: &str, new: &str) -> io::Result<usize> {
+        if !is_tag(new) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, self.locale.format(Message::InvalidTag, &[&new])))
+        }
+        let entries = self.directory.all_entries()?;
+        let tagged: Vec<String> = self.contents_of(entries).into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| self.summary(name).is_some_and(|summary| summary.tags.iter().any(|tag| tag == old)))
+            .collect();
+        let mut renamed = 0;
+        for name in tagged {
+            let mut entry = self.entry(&name)?;
+            if let Some(text) = entry.rename_tag(old, new) {
+                entry.set_text(&text);
+                self.write_entry(&name, &entry)?;
+                renamed += 1;
+            }
+        }
+        if self.tag.as_deref() == Some(old) {
+            self.set_tag(Some(new.to_string()));
+        }
+        Ok(renamed)
+    }
+
+    #[inline]
+    pub fn filter(&self) -> DateFilter {
+        self.directory.filter()
+    }
+
+    #[inline]
+    pub fn set_filter(&mut self, filter: DateFilter) {
+        self.directory.set_filter(filter)
+    }
+
+    pub fn read(&mut self) -> io::Result<()> {
+        if self.sort == Sort::Modified {
+            // Sorting by modification time needs every entry decrypted first
+            let entries = self.directory.entries()?;
+            self.contents_of(entries);
+        }
+        let entries = self.entries();
         if let Some(encryption) = &self.encryption {
-            self.directory.read(|path| {
-                let encrypted = fs::read(path)?;
-                if let Ok(decrypted) = encryption.decrypt(encrypted) {
-                    println!("{}", decrypted);
+            for name in entries {
+                if let Some(content) = self.contents.get(&name) {
+                    println!("{}", content);
+                    continue
                 }
-                Ok(())
-            })?;
+                let encrypted = fs::read(self.directory.entry_path(&name))?;
+                if let Ok(entry) = encryption.decrypt(encrypted).map(|decrypted| Entry::deserialize(&name, &decrypted, self.locale)) {
+                    println!("{}", entry.map_or_else(|error| error, |entry| entry.to_text()));
+                }
+            }
         }
         Ok(())
     }
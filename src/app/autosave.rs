@@ -0,0 +1,116 @@
+use std::{io, thread};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use super::encryption::Encryption;
+use super::file::drafts::Drafts;
+
+enum Request {
+    /// Write the text as the draft
+    Save(String, String),
+    /// Remove the draft, then say how that went
+    Discard(String, Sender<io::Result<()>>),
+}
+
+impl Request {
+    fn draft(&self) -> &str {
+        match self {
+            Request::Save(draft, _) | Request::Discard(draft, _) => draft,
+        }
+    }
+}
+
+/// Encrypts and writes the builtin editor's drafts on a background thread, so scrypt doesn't
+/// freeze the editor every autosave. Requests are handled in order, so a draft discarded after
+/// it was saved stays discarded, and a save is skipped when a newer request for its draft waits.
+pub struct Autosaver {
+    requests: Sender<Request>,
+    /// Why drafts couldn't be written
+    errors: Receiver<io::Error>,
+}
+
+impl Autosaver {
+    pub fn new(encryption: Encryption, drafts: Drafts) -> Self {
+        let (requests, worker_requests) = mpsc::channel::<Request>();
+        let (worker_errors, errors) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(request) = worker_requests.recv() {
+                let mut batch: Vec<Request> = vec![request];
+                batch.extend(worker_requests.try_iter());
+                for (index, request) in batch.iter().enumerate() {
+                    match request {
+                        Request::Save(draft, _) if batch[index + 1..].iter().any(|newer| newer.draft() == draft) => {}
+                        Request::Save(draft, text) => {
+                            let written = encryption.encrypt(text).map_err(io::Error::other)
+                                .and_then(|encrypted| drafts.write(draft, &encrypted));
+                            if let Err(error) = written {
+                                if worker_errors.send(error).is_err() {
+                                    return
+                                }
+                            }
+                        }
+                        Request::Discard(draft, done) => {
+                            let _ = done.send(drafts.remove(draft));
+                        }
+                    }
+                }
+            }
+        });
+        Autosaver {
+            requests,
+            errors,
+        }
+    }
+
+    /// Write `text` as the draft `draft` in the background.
+    pub fn save(&self, draft: &str, text: &str) {
+        let _ = self.requests.send(Request::Save(draft.to_string(), text.to_string()));
+    }
+
+    /// Remove the draft `draft` once the saves asked for before are done, waiting for it.
+    pub fn discard(&self, draft: &str) -> io::Result<()> {
+        let (done, result) = mpsc::channel();
+        self.requests.send(Request::Discard(draft.to_string(), done))
+            .map_err(|_| io::Error::other("the autosaver stopped"))?;
+        result.recv().map_err(|_| io::Error::other("the autosaver stopped"))?
+    }
+
+    /// Why drafts couldn't be written since the last call, if they couldn't.
+    pub fn error(&self) -> Option<io::Error> {
+        self.errors.try_iter().last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    #[test]
+    fn drafts_round_trip() {
+        let dir = env::temp_dir().join(format!("jou-drafts-{:016x}", rand::random::<u64>()));
+        let drafts = Drafts::new(&dir);
+        assert!(drafts.names().unwrap().is_empty());
+        drafts.write("new-2024-01-02_10-00-00Z", b"second").unwrap();
+        drafts.write("2024-01-01_09-00-00Z", b"first").unwrap();
+        assert_eq!(drafts.names().unwrap(), ["2024-01-01_09-00-00Z", "new-2024-01-02_10-00-00Z"]);
+        assert_eq!(fs::read(drafts.draft_path("2024-01-01_09-00-00Z")).unwrap(), b"first");
+        drafts.remove("2024-01-01_09-00-00Z").unwrap();
+        drafts.remove("2024-01-01_09-00-00Z").unwrap();
+        assert_eq!(drafts.names().unwrap(), ["new-2024-01-02_10-00-00Z"]);
+
+        let encryption = Encryption::new("passphrase").with_work_factor(Some(1)).unwrap();
+        let autosaver = Autosaver::new(encryption.clone(), Drafts::new(&dir));
+        autosaver.save("new-2024-01-03_08-00-00Z", "older text");
+        autosaver.save("new-2024-01-03_08-00-00Z", "newer text");
+        // Discarding waits for the saves asked for before it
+        autosaver.discard("new-2024-01-02_10-00-00Z").unwrap();
+        assert_eq!(drafts.names().unwrap(), ["new-2024-01-03_08-00-00Z"]);
+        let encrypted = fs::read(drafts.draft_path("new-2024-01-03_08-00-00Z")).unwrap();
+        assert_eq!(encryption.decrypt(encrypted).unwrap(), "newer text");
+        autosaver.save("new-2024-01-03_08-00-00Z", "discarded text");
+        autosaver.discard("new-2024-01-03_08-00-00Z").unwrap();
+        assert!(drafts.names().unwrap().is_empty());
+        assert!(autosaver.error().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,26 +1,59 @@
-use age::secrecy::Secret;
-use std::io::{Read, Write};
+use age::secrecy::SecretString;
+use std::io::{self, Read, Write};
+use std::iter;
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
 use age::{EncryptError, DecryptError};
 
+/// scrypt work factors (log2 of N) age can encrypt with
+pub const WORK_FACTORS: RangeInclusive<u8> = 1..=63;
+
 #[derive(Clone)]
 pub struct Encryption {
-    secret: Secret<String>,
+    secret: SecretString,
+    work_factor: Option<u8>,
+    max_work_factor: Option<u8>,
 }
 
 impl Encryption {
-    pub fn new<S>(passphrase: S) -> Self where 
+    pub fn new<S>(passphrase: S) -> Self where
     S: ToString {
-        let secret = Secret::new(passphrase.to_string());
+        let secret = SecretString::from(passphrase.to_string());
         Encryption {
             secret,
+            work_factor: None,
+            max_work_factor: None,
         }
     }
 
+    /// Encrypt with an scrypt work factor of `2^log_n`, instead of the one age picks to take
+    /// about a second on this machine. Fails when `log_n` is outside `WORK_FACTORS`.
+    pub fn with_work_factor(mut self, log_n: Option<u8>) -> io::Result<Self> {
+        if let Some(log_n) = log_n.filter(|log_n| !WORK_FACTORS.contains(log_n)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("work factor {} is outside {}..={}", log_n, WORK_FACTORS.start(), WORK_FACTORS.end()),
+            ))
+        }
+        self.work_factor = log_n;
+        Ok(self)
+    }
+
+    /// Refuse to decrypt files with a work factor above `2^log_n`.
+    pub fn with_max_work_factor(mut self, log_n: Option<u8>) -> Self {
+        self.max_work_factor = log_n;
+        self
+    }
+
     pub fn encrypt<S: AsRef<str>>(&self, input: S) -> Result<Vec<u8>, EncryptError> {
-        let encryptor = age::Encryptor::with_user_passphrase(self.secret.clone());
+        let mut recipient = age::scrypt::Recipient::new(self.secret.clone());
+        if let Some(log_n) = self.work_factor {
+            recipient.set_work_factor(log_n);
+        }
+        let encryptor = age::Encryptor::with_recipients(iter::once(&recipient as _))?;
 
         let mut encrypted = vec![];
-        let mut writer = encryptor.wrap_output(&mut encrypted).unwrap();
+        let mut writer = encryptor.wrap_output(&mut encrypted)?;
         writer.write_all(input.as_ref().as_bytes())?;
         writer.finish()?;
 
@@ -28,15 +61,28 @@ impl Encryption {
     }
 
     pub fn decrypt(&self, encrypted: Vec<u8>) -> Result<String, DecryptError> {
-        let decryptor = match age::Decryptor::new(&encrypted as &[u8]) {
-            Ok(age::Decryptor::Passphrase(d)) => d,
-            _ => unreachable!(),
-        };
+        let decryptor = age::Decryptor::new(&encrypted as &[u8])?;
+        let mut identity = age::scrypt::Identity::new(self.secret.clone());
+        if let Some(log_n) = self.max_work_factor {
+            identity.set_max_work_factor(log_n);
+        }
 
         let mut decrypted = vec![];
-        let mut reader = decryptor.decrypt(&self.secret, None)?;
+        let mut reader = decryptor.decrypt(iter::once(&identity as _))?;
         reader.read_to_end(&mut decrypted)?;
 
-        Ok(String::from_utf8(decrypted).unwrap())
+        String::from_utf8(decrypted)
+            .map_err(|error| DecryptError::Io(io::Error::new(io::ErrorKind::InvalidData, error)))
     }
 }
+
+/// How long decrypting a file encrypted with a work factor of `2^log_n` takes on this machine.
+pub fn unlock_time(log_n: u8) -> io::Result<Duration> {
+    let encryption = Encryption::new("jou bench-kdf")
+        .with_work_factor(Some(log_n))?
+        .with_max_work_factor(Some(log_n));
+    let encrypted = encryption.encrypt("").map_err(io::Error::other)?;
+    let start = Instant::now();
+    encryption.decrypt(encrypted).map_err(io::Error::other)?;
+    Ok(start.elapsed())
+}
@@ -0,0 +1,346 @@
+use std::{borrow::Cow, collections::BTreeMap, ops::RangeInclusive};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::locale::{Locale, Message};
+use super::file::date;
+use super::summary::{fields, parse_tags, rename_hashtag, set_field, split_front_matter};
+
+/// Version of the envelope new entries are written in
+pub const VERSION: u32 = 1;
+/// Moods an entry can have. Other values of `mood` are kept as plain fields, and rejected on save
+/// while `mood` is a configured field
+pub const MOOD: RangeInclusive<u8> = 1..=10;
+
+/// `value` as an entry's mood, when it's a whole number in `MOOD`.
+pub fn parse_mood(value: &str) -> Option<u8> {
+    value.parse().ok().filter(|mood| MOOD.contains(mood))
+}
+
+/// A journal entry. It's stored as a versioned TOML envelope inside the ciphertext, and edited as
+/// text: the metadata as a `---` front-matter block, followed by the body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub id: String,
+    pub created: DateTime<FixedOffset>,
+    pub modified: DateTime<FixedOffset>,
+    /// How many times the entry has been edited since it was written
+    #[serde(default)]
+    pub edits: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mood: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Front-matter fields without a meaning of their own, kept as they are
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    /// The front-matter block exactly as written, `---` lines included, that the metadata above is
+    /// read from. Entries saved before it was kept have their block rebuilt from the metadata.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub front_matter: String,
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    entry: Entry,
+}
+
+impl Entry {
+    /// A new entry written now, from its text.
+    pub fn new(text: &str) -> Self {
+        let now = date::current().fixed_offset();
+        Entry::from_text(random_id(), now, text)
+    }
+
+    fn from_text(id: String, created: DateTime<FixedOffset>, text: &str) -> Self {
+        let mut entry = Entry {
+            id,
+            created,
+            modified: created,
+            edits: 0,
+            title: None,
+            tags: vec![],
+            mood: None,
+            author: None,
+            fields: BTreeMap::new(),
+            front_matter: String::new(),
+            body: String::new(),
+        };
+        entry.set_text(text);
+        entry
+    }
+
+    /// Replace the metadata and body with the ones in `text`, keeping the id and creation time.
+    pub fn set_text(&mut self, text: &str) {
+        let (front_matter, body) = split_front_matter(text);
+        self.title = None;
+        self.tags = vec![];
+        self.mood = None;
+        self.author = None;
+        self.fields = BTreeMap::new();
+        for (key, value) in fields(front_matter) {
+            match key {
+                "title" if !value.is_empty() => self.title = Some(value.to_string()),
+                "tags" => self.tags = parse_tags(value),
+                "author" if !value.is_empty() => self.author = Some(value.to_string()),
+                "mood" if parse_mood(value).is_some() => self.mood = parse_mood(value),
+                key => {
+                    self.fields.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+        self.front_matter = text[..text.len() - body.len()].to_string();
+        self.body = body.to_string();
+    }
+
+    /// The entry as it's edited, with its front-matter when it has any.
+    pub fn to_text(&self) -> String {
+        format!("{}{}", self.front_matter(), self.body)
+    }
+
+    /// The front-matter block as written, or else rebuilt from the metadata.
+    fn front_matter(&self) -> Cow<'_, str> {
+        if !self.front_matter.is_empty() {
+            return Cow::Borrowed(&self.front_matter)
+        }
+        let mut front_matter = vec![];
+        if let Some(title) = &self.title {
+            front_matter.push(format!("title: {}", title));
+        }
+        if !self.tags.is_empty() {
+            front_matter.push(format!("tags: [{}]", self.tags.join(", ")));
+        }
+        if let Some(mood) = self.mood {
+            front_matter.push(format!("mood: {}", mood));
+        }
+        if let Some(author) = &self.author {
+            front_matter.push(format!("author: {}", author));
+        }
+        for (key, value) in &self.fields {
+            front_matter.push(format!("{}: {}", key, value));
+        }
+        if front_matter.is_empty() {
+            return Cow::Borrowed("")
+        }
+        Cow::Owned(format!("---\n{}\n---\n", front_matter.join("\n")))
+    }
+
+    /// Replace the entry with `text`, as an edit made now.
+    pub fn edit(&mut self, text: &str) {
+        self.set_text(text);
+        self.modified = date::current().fixed_offset();
+        self.edits += 1;
+    }
+
+    /// The entry's text with the tag `old` renamed to `new`, in the front-matter `tags` and as
+    /// `#hashtags`, or `None` when it isn't tagged `old`. An entry already tagged `new` keeps a
+    /// single `new` tag.
+    pub fn rename_tag(&self, old: &str, new: &str) -> Option<String> {
+        let mut tags = self.tags.clone();
+        if let Some(i) = tags.iter().position(|tag| tag == old) {
+            if tags.iter().any(|tag| tag == new) {
+                tags.remove(i);
+            } else {
+                tags[i] = new.to_string();
+            }
+        }
+        let body = rename_hashtag(&self.body, old, new);
+        if tags == self.tags && body == self.body {
+            return None
+        }
+        let text = format!("{}{}", self.front_matter(), body);
+        if tags == self.tags {
+            return Some(text)
+        }
+        // Only the `tags` field is rewritten, the rest of the front-matter stays as written
+        Some(set_field(&text, "tags", &format!("[{}]", tags.join(", "))))
+    }
+
+    #[inline]
+    pub fn times(&self) -> Times {
+        Times {
+            created: self.created,
+            modified: self.modified,
+            edits: self.edits,
+        }
+    }
+
+    /// The plaintext that gets encrypted.
+    pub fn serialize(&self) -> String {
+        let envelope = Envelope {
+            version: VERSION,
+            entry: self.clone(),
+        };
+        toml::to_string(&envelope).unwrap()
+    }
+
+    /// Read the decrypted entry `name`. Entries written before envelopes existed are plain text,
+    /// and are read as entries created at the time in their name. Errors are in `locale`.
+    pub fn deserialize(name: &str, plaintext: &str, locale: Locale) -> Result<Self, String> {
+        match envelope_version(plaintext) {
+            Some(version) if version > VERSION => {
+                Err(locale.format(Message::NewerEntryVersion, &[&name, &version]))
+            }
+            Some(_) => toml::from_str::<Envelope>(plaintext)
+                .map(|envelope| envelope.entry)
+                .map_err(|error| format!("{}: {}", name, error)),
+            None => {
+                let created = date::parse(name).unwrap_or_else(date::current).fixed_offset();
+                Ok(Entry::from_text(name.to_string(), created, plaintext))
+            }
+        }
+    }
+
+    /// Whether `plaintext` is an entry from before envelopes, that `jou migrate` would upgrade.
+    #[inline]
+    pub fn is_legacy(plaintext: &str) -> bool {
+        envelope_version(plaintext).is_none()
+    }
+}
+
+/// When an entry was written and last edited.
+#[derive(Debug, Clone, Copy)]
+pub struct Times {
+    pub created: DateTime<FixedOffset>,
+    pub modified: DateTime<FixedOffset>,
+    pub edits: u32,
+}
+
+impl Times {
+    /// Like `edited 3 days later (2 edits)`, or `None` for entries never edited.
+    pub fn edited(&self, locale: Locale) -> Option<String> {
+        let later = match self.edits {
+            0 => return None,
+            _ => date::later(self.created, self.modified, locale),
+        };
+        match self.edits {
+            1 => Some(locale.format(Message::Edited, &[&later])),
+            edits => Some(locale.format(Message::EditedTimes, &[&later, &edits])),
+        }
+    }
+}
+
+/// The version of the envelope `plaintext` is, or `None` when it isn't one.
+fn envelope_version(plaintext: &str) -> Option<u32> {
+    if !plaintext.starts_with("version = ") {
+        return None
+    }
+    let table: toml::Table = toml::from_str(plaintext).ok()?;
+    if !table.get("entry")?.is_table() {
+        return None
+    }
+    table.get("version")?.as_integer()?.try_into().ok()
+}
+
+fn random_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "---\ntitle: A day\ntags: [work, home]\nmood: 7\nweather: sunny\n---\nbody\n";
+
+    #[test]
+    fn envelope_round_trip() {
+        let mut entry = Entry::new(TEXT);
+        entry.edit(&TEXT.replace("body", "edited body"));
+        let plaintext = entry.serialize();
+        assert_eq!(envelope_version(&plaintext), Some(VERSION));
+        assert!(!Entry::is_legacy(&plaintext));
+
+        let read = Entry::deserialize("name", &plaintext, Locale::En).unwrap();
+        assert_eq!(read.id, entry.id);
+        assert_eq!(read.created, entry.created);
+        assert_eq!(read.modified, entry.modified);
+        assert_eq!(read.edits, 1);
+        assert_eq!(read.title.as_deref(), Some("A day"));
+        assert_eq!(read.tags, ["work", "home"]);
+        assert_eq!(read.mood, Some(7));
+        assert_eq!(read.fields.get("weather").map(String::as_str), Some("sunny"));
+        assert_eq!(read.to_text(), TEXT.replace("body", "edited body"));
+    }
+
+    #[test]
+    fn newer_envelopes_are_rejected() {
+        let plaintext = Entry::new("body").serialize().replacen("version = 1", "version = 2", 1);
+        assert!(Entry::deserialize("name", &plaintext, Locale::En).is_err());
+    }
+
+    #[test]
+    fn legacy_plain_text() {
+        let name = "2024-03-04_10-30-00";
+        let entry = Entry::deserialize(name, TEXT, Locale::En).unwrap();
+        assert_eq!(entry.id, name);
+        assert_eq!(entry.created, date::parse(name).unwrap().fixed_offset());
+        assert_eq!(entry.edits, 0);
+        assert_eq!(entry.title.as_deref(), Some("A day"));
+        assert_eq!(entry.to_text(), TEXT);
+        assert!(Entry::is_legacy(TEXT));
+    }
+
+    #[test]
+    fn legacy_plain_text_looking_like_an_envelope() {
+        for text in [
+            "version = 2 of the plan is ready",
+            "version = 3\n",
+            "version = 1\nentry = \"not a table\"\n",
+            "version = \"one\"\n[entry]\nbody = \"\"\n",
+        ] {
+            assert_eq!(envelope_version(text), None, "{text:?}");
+            let entry = Entry::deserialize("2024-03-04_10-30-00", text, Locale::En).unwrap();
+            assert_eq!(entry.body, text);
+        }
+    }
+
+    #[test]
+    fn front_matter_is_kept_as_written() {
+        let text = "---\nweather: \"sunny\"\ntitle: A day\nnote without a colon\ntags:\n  - work\n  - home\nweather: rainy\n---\nbody\n";
+        let mut entry = Entry::new(text);
+        assert_eq!(entry.title.as_deref(), Some("A day"));
+        assert_eq!(entry.tags, ["work", "home"]);
+        assert_eq!(entry.to_text(), text);
+        let read = Entry::deserialize("name", &entry.serialize(), Locale::En).unwrap();
+        assert_eq!(read.to_text(), text);
+        entry.edit("no front-matter any more");
+        assert_eq!(entry.to_text(), "no front-matter any more");
+        assert!(entry.tags.is_empty());
+    }
+
+    #[test]
+    fn legacy_text_starting_with_a_rule() {
+        let text = "---\nA paragraph between rules\n---\nthe rest\n";
+        let entry = Entry::deserialize("2024-03-04_10-30-00", text, Locale::En).unwrap();
+        assert_eq!(entry.to_text(), text);
+        let read = Entry::deserialize("2024-03-04_10-30-00", &entry.serialize(), Locale::En).unwrap();
+        assert_eq!(read.to_text(), text);
+    }
+
+    #[test]
+    fn envelopes_without_front_matter_rebuild_it() {
+        let mut entry = Entry::new(TEXT);
+        entry.front_matter = String::new();
+        assert_eq!(entry.to_text(), TEXT);
+    }
+
+    #[test]
+    fn renaming_tags_keeps_the_rest() {
+        let text = "---\nweather: sunny\nnote without a colon\ntags:\n  - work\n  - home\n---\n#work body";
+        let entry = Entry::new(text);
+        assert_eq!(
+            entry.rename_tag("work", "job").unwrap(),
+            "---\nweather: sunny\nnote without a colon\ntags: [job, home]\n---\n#job body",
+        );
+        assert_eq!(entry.rename_tag("home", "work").unwrap(), text.replace("  - work\n  - home\n", "").replace("tags:\n", "tags: [work]\n"));
+        assert_eq!(entry.rename_tag("travel", "trip"), None);
+        let hashtag_only = Entry::new("---\ntitle: a\n---\n#travel");
+        assert_eq!(hashtag_only.rename_tag("travel", "trip").unwrap(), "---\ntitle: a\n---\n#trip");
+    }
+}
@@ -1,11 +1,22 @@
-use std::{path::PathBuf, fs::{create_dir_all, self}, io};
+use std::{path::{Path, PathBuf}, fs::{create_dir_all, self}, io::{self, Seek, Write}, collections::HashSet};
 use std::convert::TryFrom;
-mod date;
+use rand::{rngs::OsRng, RngCore};
+pub mod date;
+pub mod trash;
+pub mod history;
+pub mod drafts;
+use chrono::NaiveDate;
+use date::{Calendar, DateFilter};
+use trash::Trash;
+use history::History;
+use drafts::Drafts;
 
 pub struct Directory {
     path: PathBuf,
     entries: Vec<String>,
     last_len: usize,
+    filter: DateFilter,
+    matches: Option<HashSet<String>>,
 }
 #[derive(Debug)]
 pub enum DirectoryError {
@@ -14,6 +25,28 @@ pub enum DirectoryError {
 }
 type Error = DirectoryError;
 
+/// Names starting with a dot are jou's own files (like the search index), not entries.
+#[inline]
+fn is_entry(entry: &fs::DirEntry) -> bool {
+    !entry.file_name().to_string_lossy().starts_with('.')
+}
+
+/// Overwrite the file at `path` with random bytes and then zeros, syncing each pass to disk, before
+/// unlinking it.
+pub fn shred(path: &Path) -> io::Result<()> {
+    let mut file = fs::File::options().write(true).open(path)?;
+    let len = file.metadata()?.len() as usize;
+    let mut random = vec![0; len];
+    OsRng.fill_bytes(&mut random);
+    for pass in [random, vec![0; len]] {
+        file.rewind()?;
+        file.write_all(&pass)?;
+        file.sync_all()?;
+    }
+    drop(file);
+    fs::remove_file(path)
+}
+
 impl TryFrom<&str> for Directory {
     type Error = Error;
     fn try_from(value: &str) -> Result<Self, Error> {
@@ -31,10 +64,8 @@ impl TryFrom<String> for Directory {
 
 impl Directory {
     pub fn new(path: PathBuf) -> Result<Self, Error> {
-        if !path.exists() {
-            if create_dir_all(&path).is_err() {
-                return Err(DirectoryError::CreationFailed);
-            }
+        if !path.exists() && create_dir_all(&path).is_err() {
+            return Err(DirectoryError::CreationFailed);
         }
         if path.is_file() {
             Err(DirectoryError::IsFile)
@@ -43,6 +74,8 @@ impl Directory {
                 path,
                 last_len: 0,
                 entries: vec![],
+                filter: DateFilter::default(),
+                matches: None,
             })
         }
     }
@@ -54,30 +87,78 @@ impl Directory {
     }
     
     pub fn len(&self) -> usize {
-        let count = {
-            let mut count = 0;
-            for _ in fs::read_dir(&self.path).unwrap() {
+        let mut count = 0;
+        for entry in fs::read_dir(&self.path).unwrap().flatten() {
+            if is_entry(&entry) {
                 count+=1
             }
-            count
-        };
-
+        }
         count
     }
 
+    #[inline]
+    pub fn filter(&self) -> DateFilter {
+        self.filter
+    }
+
+    #[inline]
+    pub fn set_filter(&mut self, filter: DateFilter) {
+        self.filter = filter;
+    }
+
+    /// Only show the entries named in `matches`, on top of the date filter.
+    #[inline]
+    pub fn set_matches(&mut self, matches: Option<HashSet<String>>) {
+        self.matches = matches;
+    }
+
+    #[inline]
+    pub fn entry_path<S: AsRef<str>>(&self, name: S) -> PathBuf {
+        self.path.join(name.as_ref())
+    }
+
+    #[inline]
+    pub fn trash(&self) -> Trash {
+        Trash::new(&self.path)
+    }
+
+    #[inline]
+    pub fn history(&self) -> History {
+        History::new(&self.path)
+    }
+
+    #[inline]
+    pub fn drafts(&self) -> Drafts {
+        Drafts::new(&self.path)
+    }
+
+    #[inline]
+    pub fn index_path(&self) -> PathBuf {
+        self.path.join(".index")
+    }
+
+    #[inline]
+    pub fn stats_path(&self) -> PathBuf {
+        self.path.join(".stats")
+    }
+
     pub fn update_entries(&mut self) -> io::Result<()>{
         self.entries = vec![];
         for entry in fs::read_dir(&self.path).unwrap() {
             let entry = entry?;
+            if !is_entry(&entry) {
+                continue;
+            }
             if let Some(name) = entry.path().file_name() {
                 self.entries.push(name.to_str().unwrap().to_string());
             }
         }
-        self.entries.sort_by(|a, b| b.cmp(a));
+        self.entries.sort_by_cached_key(|name| date::newest_first(name));
         Ok(())
     }
 
-    pub fn entries(&mut self) -> io::Result<Vec<String>> {
+    /// Every entry, newest first, ignoring the filters.
+    pub fn all_entries(&mut self) -> io::Result<Vec<String>> {
         let len = self.len();
         if len != self.last_len {
             self.update_entries()?;
@@ -86,21 +167,76 @@ impl Directory {
         Ok(self.entries.clone())
     }
 
-    pub fn read<F>(&self, callback: F) -> io::Result<()> where 
-        F: Fn(PathBuf)->io::Result<()> {
-        for entry in fs::read_dir(&self.path)? {
-            let entry = entry?;
-            callback(entry.path())?;
-        }
-        Ok(())
+    pub fn entries(&mut self) -> io::Result<Vec<String>> {
+        Ok(self.all_entries()?.into_iter()
+            .filter(|name| self.filter.matches(name))
+            .filter(|name| self.matches.as_ref().is_none_or(|matches| matches.contains(name)))
+            .collect())
     }
 
-    pub fn nth_path(&mut self, n: usize) -> Option<PathBuf> {
-        for (i, entry) in self.entries().unwrap().iter().enumerate() {
-            if i == n {
-                return Some(self.path.join(entry))
-            }
+    /// Entries written on the day of the year of `today` in earlier years of `calendar`, newest
+    /// first. On the last day of a month, the days this year's month doesn't have count too, like
+    /// February 29th. Ignores the filters.
+    pub fn on_this_day(&mut self, today: NaiveDate, calendar: Calendar) -> io::Result<Vec<String>> {
+        let (year, month, day) = calendar.ymd(today);
+        let last_day = day == calendar.month_len(year, month);
+        Ok(self.all_entries()?.into_iter()
+            .filter(|name| {
+                let Some(created) = date::parse(name) else {
+                    return false
+                };
+                let (entry_year, entry_month, entry_day) = calendar.ymd(created.date_naive());
+                entry_year < year && entry_month == month && (entry_day == day || last_day && entry_day > day)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// A directory with an empty entry written at noon on each of `days`.
+    fn directory(days: &[NaiveDate]) -> (PathBuf, Directory) {
+        let path = env::temp_dir().join(format!("jou-on-this-day-{:016x}", rand::random::<u64>()));
+        let directory = Directory::new(path.clone()).unwrap();
+        for day in days {
+            fs::write(path.join(day.format("%Y-%m-%d_12-00-00").to_string()), "").unwrap();
         }
-        None
+        (path, directory)
+    }
+
+    fn on_this_day(days: &[NaiveDate], today: NaiveDate, calendar: Calendar) -> Vec<NaiveDate> {
+        let (path, mut directory) = directory(days);
+        let entries = directory.on_this_day(today, calendar).unwrap();
+        fs::remove_dir_all(path).unwrap();
+        entries.iter().map(|name| date::parse(name).unwrap().date_naive()).collect()
+    }
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn gregorian_on_this_day() {
+        let days = [day(2020, 2, 29), day(2023, 2, 28), day(2023, 2, 27), day(2023, 3, 1), day(2025, 2, 28)];
+        // February 29th counts on the 28th in years without it
+        assert_eq!(on_this_day(&days, day(2025, 2, 28), Calendar::Gregorian), [day(2023, 2, 28), day(2020, 2, 29)]);
+        assert_eq!(on_this_day(&days, day(2024, 2, 28), Calendar::Gregorian), [day(2023, 2, 28)]);
+        assert_eq!(on_this_day(&days, day(2024, 2, 29), Calendar::Gregorian), [day(2020, 2, 29)]);
+        assert_eq!(on_this_day(&days, day(2026, 3, 1), Calendar::Gregorian), [day(2023, 3, 1)]);
+    }
+
+    #[test]
+    fn jalali_on_this_day() {
+        let jalali = |year, month, day| Calendar::Jalali.date(year, month, day).unwrap();
+        // Esfand has 30 days in 1399 and 1403, and 29 in 1400 and 1402
+        let days = [jalali(1399, 12, 30), jalali(1400, 12, 29), jalali(1401, 12, 28)];
+        assert_eq!(on_this_day(&days, jalali(1402, 12, 29), Calendar::Jalali), [jalali(1400, 12, 29), jalali(1399, 12, 30)]);
+        assert_eq!(on_this_day(&days, jalali(1403, 12, 29), Calendar::Jalali), [jalali(1400, 12, 29)]);
+        assert_eq!(on_this_day(&days, jalali(1403, 12, 30), Calendar::Jalali), [jalali(1399, 12, 30)]);
+        // A Jalali day of the year isn't the same Gregorian one every year
+        assert_eq!(on_this_day(&days, jalali(1402, 12, 28), Calendar::Jalali), [jalali(1401, 12, 28)]);
     }
 }
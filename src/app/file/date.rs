@@ -1,11 +1,36 @@
-use chrono::{Local ,DateTime, FixedOffset};
-use chrono::format::ParseError;
+use std::{borrow::Cow, cmp::Reverse, fmt};
+use chrono::{Local, DateTime, NaiveDate, NaiveDateTime, NaiveTime, Datelike, Duration, Months, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::locale::{Locale, Message};
+pub mod jalali;
 // const FORMAT: &str = "%Y-%m-%d.%H:%M:%S";
+/// Names written before they had a zone, in the local time they were written at
 const FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+/// Names are written in UTC, so they sort and display right wherever they were written
+const UTC_FORMAT: &str = "%Y-%m-%d_%H-%M-%SZ";
+const OFFSET_FORMAT: &str = "%Y-%m-%d_%H-%M-%S%z";
+const INPUT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// The time in the name of an entry (or revision, or draft), in the local zone. Names are in UTC,
+/// or have an offset like `+0200`; names from before either are taken to be in the current zone.
+pub fn parse(date_string: &str) -> Option<DateTime<Local>> {
+    if let Some(utc) = date_string.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, FORMAT).ok()?;
+        return Some(Utc.from_utc_datetime(&naive).with_timezone(&Local))
+    }
+    if let Ok(date) = DateTime::parse_from_str(date_string, OFFSET_FORMAT) {
+        return Some(date.with_timezone(&Local))
+    }
+    let naive = NaiveDateTime::parse_from_str(date_string, FORMAT).ok()?;
+    from_naive(naive)
+}
 
+/// Orders names newest first by the time in them, whatever zone they're in. Names that aren't
+/// dates come last.
 #[inline]
-pub fn parse(date_string: &String) -> Result<DateTime<FixedOffset>, ParseError> {
-    DateTime::parse_from_str(date_string.as_str(), FORMAT)
+pub fn newest_first(name: &str) -> Reverse<(Option<DateTime<Local>>, String)> {
+    Reverse((parse(name), name.to_string()))
 }
 
 #[inline]
@@ -21,7 +46,553 @@ pub fn current() -> DateTime<Local> {
 #[inline]
 pub fn format(input: Option<DateTime<Local>>) -> String {
     match input {
-        Some(date)=> date.format(FORMAT).to_string(),
+        Some(date)=> date.with_timezone(&Utc).format(UTC_FORMAT).to_string(),
         None => String::new(),
     }
 }
+
+/// An entry's filename rendered with the strftime `format` in `style`. Names that aren't dates are
+/// returned as is.
+pub fn display(name: &str, format: &str, style: DateStyle) -> String {
+    match parse(name) {
+        Some(date) => style.format(&date, format),
+        None => name.to_string(),
+    }
+}
+
+/// The calendar dates are shown and entered in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Calendar {
+    #[default]
+    Gregorian,
+    /// The Solar Hijri calendar, with Persian month names
+    Jalali,
+}
+
+impl Calendar {
+    /// The year, month and day of `date` in this calendar.
+    pub fn ymd(self, date: NaiveDate) -> (i32, u32, u32) {
+        match self {
+            Calendar::Gregorian => (date.year(), date.month(), date.day()),
+            Calendar::Jalali => jalali::from_gregorian(date),
+        }
+    }
+
+    pub fn date(self, year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        match self {
+            Calendar::Gregorian => NaiveDate::from_ymd_opt(year, month, day),
+            Calendar::Jalali => jalali::to_gregorian(year, month, day),
+        }
+    }
+
+    pub fn month_len(self, year: i32, month: u32) -> u32 {
+        match self {
+            Calendar::Gregorian => {
+                let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+                (first + Months::new(1) - first).num_days() as u32
+            }
+            Calendar::Jalali => jalali::month_len(year, month),
+        }
+    }
+
+    #[inline]
+    pub fn first_of_month(self, date: NaiveDate) -> NaiveDate {
+        let (year, month, _) = self.ymd(date);
+        self.date(year, month, 1).unwrap()
+    }
+
+    /// `date` moved by `months`, on the same day of the month or the last day of shorter months.
+    pub fn add_months(self, date: NaiveDate, months: i32) -> Option<NaiveDate> {
+        let (year, month, day) = self.ymd(date);
+        let month = year * 12 + month as i32 - 1 + months;
+        let (year, month) = (month.div_euclid(12), month.rem_euclid(12) as u32 + 1);
+        self.date(year, month, day.min(self.month_len(year, month)))
+    }
+
+    /// The name of `month`: Persian in the Jalali calendar, in `locale` otherwise.
+    pub fn month_name(self, month: u32, locale: Locale) -> &'static str {
+        match self {
+            Calendar::Gregorian => locale.month(month),
+            Calendar::Jalali => jalali::MONTHS[month as usize - 1],
+        }
+    }
+
+    /// The first day of the week: Monday, or Saturday in Iran.
+    pub fn week_start(self) -> Weekday {
+        match self {
+            Calendar::Gregorian => Weekday::Mon,
+            Calendar::Jalali => Weekday::Sat,
+        }
+    }
+}
+
+/// How dates are shown: in which calendar, and with which digits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateStyle {
+    pub calendar: Calendar,
+    pub persian_digits: bool,
+}
+
+impl DateStyle {
+    /// `date` rendered with the strftime `format`. In the Jalali calendar the fields of the date
+    /// (`%Y`, `%m`, `%d`, `%B`, `%A`, ...) are Jalali, and names are Persian.
+    pub fn format(&self, date: &DateTime<Local>, format: &str) -> String {
+        let format = match self.calendar {
+            Calendar::Gregorian => Cow::Borrowed(format),
+            Calendar::Jalali => Cow::Owned(jalali_format(date.date_naive(), format)),
+        };
+        self.digits(&date.format(&format).to_string())
+    }
+
+    /// The day `date` rendered with the strftime `format`, like `format`.
+    pub fn format_day(&self, date: NaiveDate, format: &str) -> String {
+        let format = match self.calendar {
+            Calendar::Gregorian => Cow::Borrowed(format),
+            Calendar::Jalali => Cow::Owned(jalali_format(date, format)),
+        };
+        self.digits(&date.format(&format).to_string())
+    }
+
+    /// `text` with Persian digits, if they're wanted.
+    pub fn digits(&self, text: &str) -> String {
+        if !self.persian_digits {
+            return text.to_string()
+        }
+        text.chars()
+            .map(|c| match c.to_digit(10) {
+                Some(digit) if c.is_ascii_digit() => char::from_u32(0x06F0 + digit).unwrap(),
+                _ => c,
+            })
+            .collect()
+    }
+}
+
+/// `format` with the date fields replaced by the Jalali date of `date`, leaving the rest (like the
+/// time) to chrono.
+fn jalali_format(date: NaiveDate, format: &str) -> String {
+    let (year, month, day) = jalali::from_gregorian(date);
+    let mut result = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue
+        }
+        let padding = chars.next_if(|c| matches!(c, '-' | '_' | '0'));
+        let Some(specifier) = chars.next() else {
+            result.push('%');
+            break
+        };
+        let number = |n: u32, width: usize, default: char| match padding.unwrap_or(default) {
+            '-' => n.to_string(),
+            '_' => format!("{:>width$}", n),
+            _ => format!("{:0width$}", n),
+        };
+        let day_of_year = if month <= 6 { (month - 1) * 31 } else { 186 + (month - 7) * 30 } + day;
+        let value = match specifier {
+            'Y' => year.to_string(),
+            'y' => number(year.rem_euclid(100) as u32, 2, '0'),
+            'm' => number(month, 2, '0'),
+            'd' => number(day, 2, '0'),
+            'e' => number(day, 2, '_'),
+            'j' => number(day_of_year, 3, '0'),
+            'B' | 'b' | 'h' => jalali::MONTHS[month as usize - 1].to_string(),
+            'A' | 'a' => jalali::WEEKDAYS[date.weekday().num_days_from_monday() as usize].to_string(),
+            'F' => format!("{}-{:02}-{:02}", year, month, day),
+            _ => {
+                result.push('%');
+                result.extend(padding);
+                result.push(specifier);
+                continue
+            }
+        };
+        result.push_str(&value);
+    }
+    result
+}
+
+/// `input` with Persian and Arabic digits turned into ASCII ones.
+fn ascii_digits(input: &str) -> String {
+    input.chars()
+        .map(|c| match c as u32 {
+            digit @ 0x06F0..=0x06F9 => char::from_u32(digit - 0x06F0 + '0' as u32).unwrap(),
+            digit @ 0x0660..=0x0669 => char::from_u32(digit - 0x0660 + '0' as u32).unwrap(),
+            _ => c,
+        })
+        .collect()
+}
+
+/// A date written like `2024-03-04`, `2024-03` or `2024`, or with slashes. Years before 1700 are
+/// Jalali years, like in `1403/01/15`.
+fn numeric_date(input: &str) -> Option<(Calendar, i32, Option<u32>, Option<u32>)> {
+    let mut parts = input.split(['-', '/']);
+    let year = parts.next().filter(|year| year.len() == 4)?;
+    let year: i32 = year.parse().ok()?;
+    let month = parts.next().map(str::parse).transpose().ok()?;
+    let day = parts.next().map(str::parse).transpose().ok()?;
+    if parts.next().is_some() {
+        return None
+    }
+    let calendar = if year < 1700 { Calendar::Jalali } else { Calendar::Gregorian };
+    Some((calendar, year, month, day))
+}
+
+/// How long after `from` `to` is, like `3 days later`.
+pub fn later<Tz: TimeZone>(from: DateTime<Tz>, to: DateTime<Tz>, locale: Locale) -> String {
+    let minutes = (to - from).num_minutes().max(0);
+    let (amount, one, many) = match minutes {
+        0 => return locale.text(Message::WithinAMinute).to_string(),
+        1..=59 => (minutes, Message::MinuteLater, Message::MinutesLater),
+        60..=1439 => (minutes / 60, Message::HourLater, Message::HoursLater),
+        1440..=86_399 => (minutes / 1440, Message::DayLater, Message::DaysLater),
+        86_400..=525_599 => (minutes / 43_200, Message::MonthLater, Message::MonthsLater),
+        _ => (minutes / 525_600, Message::YearLater, Message::YearsLater),
+    };
+    match amount {
+        1 => locale.text(one).to_string(),
+        amount => locale.format(many, &[&amount]),
+    }
+}
+
+/// The `year` of entries resurfaced on this day, with how long ago it was.
+pub fn years_ago(locale: Locale, style: DateStyle, year: i32, this_year: i32) -> String {
+    let label = style.digits(&year.to_string());
+    match this_year - year {
+        1 => locale.format(Message::YearAgo, &[&label]),
+        years => locale.format(Message::YearsAgo, &[&label, &style.digits(&years.to_string())]),
+    }
+}
+
+#[inline]
+fn from_naive(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&naive).earliest()
+}
+
+#[inline]
+fn start_of_day(date: NaiveDate) -> Option<DateTime<Local>> {
+    from_naive(date.and_hms_opt(0, 0, 0)?)
+}
+
+/// A half-open span of time `[start, end)`, parsed from user input like
+/// `2024-03-04`, `2024-03`, `today`, `week` or `3d` (three days ago).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl Period {
+    /// The whole day `date`.
+    #[inline]
+    pub fn day(date: NaiveDate) -> Option<Self> {
+        Self::days(date, 1)
+    }
+
+    fn days(first: NaiveDate, count: u64) -> Option<Self> {
+        let last = first.checked_add_days(chrono::Days::new(count))?;
+        Some(Period {
+            start: start_of_day(first)?,
+            end: start_of_day(last)?,
+        })
+    }
+
+    fn months(calendar: Calendar, first: NaiveDate, count: i32) -> Option<Self> {
+        let last = calendar.add_months(first, count)?;
+        Some(Period {
+            start: start_of_day(first)?,
+            end: start_of_day(last)?,
+        })
+    }
+
+    fn relative(input: &str, today: NaiveDate) -> Option<Self> {
+        let unit = input.chars().last()?;
+        let amount: u32 = input[..input.len()-unit.len_utf8()].parse().ok()?;
+        let day = match unit {
+            'd' => today.checked_sub_days(chrono::Days::new(amount.into()))?,
+            'w' => today.checked_sub_days(chrono::Days::new(u64::from(amount)*7))?,
+            'm' => today.checked_sub_months(Months::new(amount))?,
+            'y' => today.checked_sub_months(Months::new(amount*12))?,
+            _ => return None,
+        };
+        Self::days(day, 1)
+    }
+
+    fn absolute(input: &str) -> Option<Self> {
+        if let Some((date, time)) = input.split_once(' ') {
+            let time = NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()?;
+            let (calendar, year, Some(month), Some(day)) = numeric_date(date)? else {
+                return None
+            };
+            let start = from_naive(calendar.date(year, month, day)?.and_time(time))?;
+            return Some(Period { start, end: start + Duration::minutes(1) })
+        }
+        let (calendar, year, month, day) = numeric_date(input)?;
+        match (month, day) {
+            (Some(month), Some(day)) => Self::days(calendar.date(year, month, day)?, 1),
+            (Some(month), None) => Self::months(calendar, calendar.date(year, month, 1)?, 1),
+            (None, _) => Self::months(calendar, calendar.date(year, 1, 1)?, 12),
+        }
+    }
+
+    /// Parse a period. `week`, `month` and `year` are the current ones in `calendar`. Absolute
+    /// dates before the year 1700 are Jalali, and Persian digits are accepted.
+    #[inline]
+    pub fn parse(input: &str, calendar: Calendar) -> Option<Self> {
+        Self::parse_on(input, calendar, current().date_naive())
+    }
+
+    /// Parse a period like `parse`, with an error message in `locale` when `input` isn't one.
+    pub fn parse_input(input: &str, calendar: Calendar, locale: Locale) -> Result<Self, String> {
+        Period::parse(input, calendar).ok_or_else(|| locale.format(Message::InvalidDate, &[&input]))
+    }
+
+    fn parse_on(input: &str, calendar: Calendar, today: NaiveDate) -> Option<Self> {
+        let input = ascii_digits(input.trim());
+        let input = input.as_str();
+        match input {
+            // The current moment as a period would be empty, so `now` is the day it's in
+            "now" | "today" => Self::days(today, 1),
+            "yesterday" => Self::days(today.pred_opt()?, 1),
+            "week" => {
+                let days = (today.weekday().num_days_from_monday() + 7
+                    - calendar.week_start().num_days_from_monday()) % 7;
+                Self::days(today - Duration::days(days.into()), 7)
+            }
+            "month" => Self::months(calendar, calendar.first_of_month(today), 1),
+            "year" => {
+                let (year, _, _) = calendar.ymd(today);
+                Self::months(calendar, calendar.date(year, 1, 1)?, 12)
+            }
+            input => Self::relative(input, today).or_else(|| Self::absolute(input)),
+        }
+    }
+}
+
+/// Restricts entries to the ones created inside `[since, until)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateFilter {
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+}
+
+impl DateFilter {
+    pub fn new(since: Option<Period>, until: Option<Period>, on: Option<Period>) -> Self {
+        match on {
+            Some(period) => DateFilter {
+                since: Some(period.start),
+                until: Some(period.end),
+            },
+            None => DateFilter {
+                since: since.map(|period| period.start),
+                until: until.map(|period| period.end),
+            },
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn contains(&self, date: &DateTime<Local>) -> bool {
+        self.since.is_none_or(|since| *date >= since) &&
+            self.until.is_none_or(|until| *date < until)
+    }
+
+    /// Whether an entry with filename `name` passes the filter. Names that aren't dates only pass
+    /// an empty filter.
+    pub fn matches(&self, name: &str) -> bool {
+        if self.is_empty() {
+            return true
+        }
+        match parse(name) {
+            Some(date) => self.contains(&date),
+            None => false,
+        }
+    }
+}
+
+impl DateFilter {
+    /// Parses `since..until` (either side may be omitted) or a single period, in `calendar`.
+    /// Errors are in `locale`.
+    pub fn parse(input: &str, calendar: Calendar, locale: Locale) -> Result<Self, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(DateFilter::default())
+        }
+        let parse_side = |side: &str| -> Result<Option<Period>, String> {
+            if side.trim().is_empty() {
+                Ok(None)
+            } else {
+                Period::parse_input(side, calendar, locale).map(Some)
+            }
+        };
+        match input.split_once("..") {
+            Some((since, until)) => Ok(DateFilter::new(parse_side(since)?, parse_side(until)?, None)),
+            None => Ok(DateFilter::new(None, None, Some(Period::parse_input(input, calendar, locale)?))),
+        }
+    }
+
+    /// The filter as `since..until`, written so it parses back to itself.
+    pub fn display(&self, style: DateStyle) -> String {
+        let side = |date: Option<DateTime<Local>>| match date {
+            Some(date) => style.format(&date, INPUT_FORMAT),
+            None => String::new(),
+        };
+        format!("{}..{}", side(self.since), side(self.until))
+    }
+}
+
+impl fmt::Display for DateFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display(DateStyle::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(year: i32, month: u32, day: u32) -> DateTime<Local> {
+        start_of_day(NaiveDate::from_ymd_opt(year, month, day).unwrap()).unwrap()
+    }
+
+    fn period(input: &str) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        Period::parse(input, Calendar::Gregorian).map(|period| (period.start, period.end))
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(parse("2024-03-04_10-30-00Z"), Some(utc(2024, 3, 4, 10, 30).with_timezone(&Local)));
+        assert_eq!(parse("2024-03-04_12-30-00+0200"), Some(utc(2024, 3, 4, 10, 30).with_timezone(&Local)));
+        assert_eq!(parse("2024-03-04_05-30-00-0500"), Some(utc(2024, 3, 4, 10, 30).with_timezone(&Local)));
+        // Names from before zones are in local time
+        let legacy = Local.with_ymd_and_hms(2024, 3, 4, 10, 30, 0).single();
+        assert_eq!(parse("2024-03-04_10-30-00"), legacy);
+        for name in ["note", "2024-13-01_00-00-00Z", "2024-03-04_10-30-00+02", "2024-03-04 10:30", ""] {
+            assert_eq!(parse(name), None, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn names_round_trip() {
+        assert_eq!(format(parse("2024-03-04_10-30-00Z")), "2024-03-04_10-30-00Z");
+        // Names with an offset or without a zone are written back in UTC
+        assert_eq!(format(parse("2024-03-04_12-30-00+0200")), "2024-03-04_10-30-00Z");
+        let legacy = Local.with_ymd_and_hms(2024, 3, 4, 10, 30, 0).single().unwrap();
+        assert_eq!(format(parse("2024-03-04_10-30-00")), legacy.with_timezone(&Utc).format(UTC_FORMAT).to_string());
+        let now = current();
+        assert_eq!(parse(&format(Some(now))).map(|date| date.timestamp()), Some(now.timestamp()));
+        assert_eq!(format(None), "");
+    }
+
+    #[test]
+    fn newest_first_across_zones() {
+        let mut names = vec![
+            "note",
+            "2024-03-02_12-00-00",
+            "2024-03-04_12-00-00+0200",
+            "2024-03-06_00-00-00",
+            "2024-03-04_10-30-00Z",
+            "2024-03-04_06-00-00-0500",
+        ];
+        names.sort_by_key(|name| newest_first(name));
+        assert_eq!(names, [
+            "2024-03-06_00-00-00",
+            "2024-03-04_06-00-00-0500",
+            "2024-03-04_10-30-00Z",
+            "2024-03-04_12-00-00+0200",
+            "2024-03-02_12-00-00",
+            "note",
+        ]);
+    }
+
+    #[test]
+    fn gregorian_periods() {
+        assert_eq!(period("2024-03-04"), Some((day(2024, 3, 4), day(2024, 3, 5))));
+        assert_eq!(period("2024/03/04"), Some((day(2024, 3, 4), day(2024, 3, 5))));
+        assert_eq!(period("2024-03"), Some((day(2024, 3, 1), day(2024, 4, 1))));
+        assert_eq!(period("2024"), Some((day(2024, 1, 1), day(2025, 1, 1))));
+        assert_eq!(period("1700-01-01"), Some((day(1700, 1, 1), day(1700, 1, 2))));
+    }
+
+    #[test]
+    fn jalali_periods() {
+        assert_eq!(period("1403/01/15"), Some((day(2024, 4, 3), day(2024, 4, 4))));
+        assert_eq!(period("1403-01"), Some((day(2024, 3, 20), day(2024, 4, 20))));
+        assert_eq!(period("1403/12"), Some((day(2025, 2, 19), day(2025, 3, 21))));
+        assert_eq!(period("1403"), Some((day(2024, 3, 20), day(2025, 3, 21))));
+        assert_eq!(period("1402"), Some((day(2023, 3, 21), day(2024, 3, 20))));
+    }
+
+    #[test]
+    fn persian_digits() {
+        assert_eq!(period("۱۴۰۳/۰۱/۰۱"), period("1403/01/01"));
+        assert_eq!(period("٢٠٢٤-٠٣"), period("2024-03"));
+    }
+
+    #[test]
+    fn invalid_periods() {
+        assert_eq!(period("2024-02-30"), None);
+        assert_eq!(period("1402/12/30"), None);
+        assert_eq!(period("24-03-04"), None);
+        assert_eq!(period("2024-03-04-05"), None);
+        assert_eq!(period("someday"), None);
+    }
+
+    #[test]
+    fn relative_periods() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+        let relative = |input| Period::relative(input, today).map(|period| period.start);
+        assert_eq!(relative("3d"), Some(day(2024, 3, 17)));
+        assert_eq!(relative("2w"), Some(day(2024, 3, 6)));
+        assert_eq!(relative("1m"), Some(day(2024, 2, 20)));
+        assert_eq!(relative("1y"), Some(day(2023, 3, 20)));
+    }
+
+    #[test]
+    fn named_periods() {
+        let named = |input, calendar, today| {
+            Period::parse_on(input, calendar, today).map(|period| (period.start, period.end))
+        };
+        let wednesday = NaiveDate::from_ymd_opt(2024, 4, 10).unwrap();
+        assert_eq!(named("now", Calendar::Gregorian, wednesday), Some((day(2024, 4, 10), day(2024, 4, 11))));
+        assert_eq!(named("today", Calendar::Gregorian, wednesday), named("now", Calendar::Gregorian, wednesday));
+        assert_eq!(named("week", Calendar::Gregorian, wednesday), Some((day(2024, 4, 8), day(2024, 4, 15))));
+        assert_eq!(named("month", Calendar::Gregorian, wednesday), Some((day(2024, 4, 1), day(2024, 5, 1))));
+        assert_eq!(named("year", Calendar::Gregorian, wednesday), Some((day(2024, 1, 1), day(2025, 1, 1))));
+        assert_eq!(named("week", Calendar::Jalali, wednesday), Some((day(2024, 4, 6), day(2024, 4, 13))));
+        assert_eq!(named("month", Calendar::Jalali, wednesday), Some((day(2024, 3, 20), day(2024, 4, 20))));
+        assert_eq!(named("year", Calendar::Jalali, wednesday), Some((day(2024, 3, 20), day(2025, 3, 21))));
+
+        let saturday = NaiveDate::from_ymd_opt(2024, 4, 6).unwrap();
+        let sunday = NaiveDate::from_ymd_opt(2024, 4, 7).unwrap();
+        assert_eq!(named("week", Calendar::Jalali, saturday), Some((day(2024, 4, 6), day(2024, 4, 13))));
+        assert_eq!(named("week", Calendar::Gregorian, sunday), Some((day(2024, 4, 1), day(2024, 4, 8))));
+        let new_year = NaiveDate::from_ymd_opt(2024, 3, 19).unwrap();
+        assert_eq!(named("year", Calendar::Jalali, new_year), Some((day(2023, 3, 21), day(2024, 3, 20))));
+    }
+
+    #[test]
+    fn years_ago_labels() {
+        assert_eq!(years_ago(Locale::En, DateStyle::default(), 2023, 2024), "2023, 1 year ago");
+        assert_eq!(years_ago(Locale::En, DateStyle::default(), 2020, 2024), "2020, 4 years ago");
+        let style = DateStyle { calendar: Calendar::Jalali, persian_digits: true };
+        assert_eq!(years_ago(Locale::Fa, style, 1400, 1403), "۱۴۰۰، ۳ سال پیش");
+    }
+
+    #[test]
+    fn filters() {
+        let filter = DateFilter::parse("1403/01/01..1403/01/31", Calendar::Gregorian, Locale::En).unwrap();
+        assert_eq!(filter.since, Some(day(2024, 3, 20)));
+        assert_eq!(filter.until, Some(day(2024, 4, 20)));
+        let filter = DateFilter::parse("..2024-03", Calendar::Jalali, Locale::En).unwrap();
+        assert_eq!(filter.since, None);
+        assert_eq!(filter.until, Some(day(2024, 4, 1)));
+    }
+}
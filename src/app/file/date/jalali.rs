@@ -0,0 +1,127 @@
+//! Conversion between Gregorian dates and the Solar Hijri (Jalali) calendar, using the 33 year
+//! cycle arithmetic of `jdf`. It's exact for the years 1178 to 1633.
+use chrono::{Datelike, NaiveDate};
+
+pub const MONTHS: [&str; 12] = [
+    "فروردین", "اردیبهشت", "خرداد", "تیر", "مرداد", "شهریور",
+    "مهر", "آبان", "آذر", "دی", "بهمن", "اسفند",
+];
+
+/// Weekdays starting on Monday, like `Weekday::num_days_from_monday`
+pub const WEEKDAYS: [&str; 7] = [
+    "دوشنبه", "سه‌شنبه", "چهارشنبه", "پنجشنبه", "جمعه", "شنبه", "یکشنبه",
+];
+
+/// One letter abbreviations of `WEEKDAYS`
+pub const WEEKDAY_INITIALS: [&str; 7] = ["د", "س", "چ", "پ", "ج", "ش", "ی"];
+
+/// Days in a common Gregorian year before the start of each month
+const CUMULATIVE_DAYS: [i64; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+
+/// The Jalali year, month and day of `date`.
+pub fn from_gregorian(date: NaiveDate) -> (i32, u32, u32) {
+    let (year, month, day) = (date.year() as i64, date.month() as usize, date.day() as i64);
+    let year2 = if month > 2 { year + 1 } else { year };
+    let mut days = 355_666 + 365 * year + (year2 + 3).div_euclid(4) - (year2 + 99).div_euclid(100)
+        + (year2 + 399).div_euclid(400) + day + CUMULATIVE_DAYS[month - 1];
+    let mut jalali_year = -1595 + 33 * days.div_euclid(12_053);
+    days = days.rem_euclid(12_053);
+    jalali_year += 4 * (days / 1461);
+    days %= 1461;
+    if days > 365 {
+        jalali_year += (days - 1) / 365;
+        days = (days - 1) % 365;
+    }
+    let (month, day) = if days < 186 {
+        (1 + days / 31, 1 + days % 31)
+    } else {
+        (7 + (days - 186) / 30, 1 + (days - 186) % 30)
+    };
+    (jalali_year as i32, month as u32, day as u32)
+}
+
+/// The Gregorian date of the Jalali `year`, `month` and `day`, if it's a valid date.
+pub fn to_gregorian(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    if !(1..=12).contains(&month) || day < 1 || day > month_len(year, month) {
+        return None
+    }
+    Some(from_days(year, month, day))
+}
+
+/// Days in the Jalali `month` of `year`.
+pub fn month_len(year: i32, month: u32) -> u32 {
+    match month {
+        1..=6 => 31,
+        7..=11 => 30,
+        // Esfand has 30 days in leap years, when the day after its 29th is still in the year
+        _ => match from_gregorian(from_days(year, 12, 29).succ_opt().unwrap()) {
+            (_, 12, 30) => 30,
+            _ => 29,
+        },
+    }
+}
+
+fn from_days(year: i32, month: u32, day: u32) -> NaiveDate {
+    let year = year as i64 + 1595;
+    let month_days = if month < 7 { (month as i64 - 1) * 31 } else { (month as i64 - 7) * 30 + 186 };
+    let days = -355_668 + 365 * year + year.div_euclid(33) * 8 + (year.rem_euclid(33) + 3) / 4
+        + day as i64 + month_days;
+    // `jdf`'s day 0 is 1 January of the year 0, a leap year, so day 366 is 1 January of the year 1
+    NaiveDate::from_num_days_from_ce_opt((days - 365) as i32).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gregorian(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn known_dates() {
+        let dates = [
+            ((1357, 11, 22), gregorian(1979, 2, 11)),
+            ((1399, 12, 30), gregorian(2021, 3, 20)),
+            ((1400, 1, 1), gregorian(2021, 3, 21)),
+            ((1402, 1, 1), gregorian(2023, 3, 21)),
+            ((1402, 10, 11), gregorian(2024, 1, 1)),
+            ((1403, 1, 1), gregorian(2024, 3, 20)),
+            ((1403, 10, 12), gregorian(2025, 1, 1)),
+            ((1403, 12, 30), gregorian(2025, 3, 20)),
+            ((1404, 1, 1), gregorian(2025, 3, 21)),
+        ];
+        for ((year, month, day), date) in dates {
+            assert_eq!(from_gregorian(date), (year, month, day), "{date}");
+            assert_eq!(to_gregorian(year, month, day), Some(date), "{year}/{month}/{day}");
+        }
+    }
+
+    #[test]
+    fn leap_years() {
+        assert_eq!(month_len(1399, 12), 30);
+        assert_eq!(month_len(1402, 12), 29);
+        assert_eq!(month_len(1403, 12), 30);
+        assert_eq!(month_len(1404, 12), 29);
+        assert_eq!(to_gregorian(1402, 12, 30), None);
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert_eq!(to_gregorian(1403, 0, 1), None);
+        assert_eq!(to_gregorian(1403, 13, 1), None);
+        assert_eq!(to_gregorian(1403, 1, 0), None);
+        assert_eq!(to_gregorian(1403, 6, 32), None);
+        assert_eq!(to_gregorian(1403, 7, 31), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut date = gregorian(1990, 1, 1);
+        while date < gregorian(2040, 1, 1) {
+            let (year, month, day) = from_gregorian(date);
+            assert_eq!(to_gregorian(year, month, day), Some(date), "{date}");
+            date = date.succ_opt().unwrap();
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::{fs, io, path::{Path, PathBuf}};
+use super::{date, shred};
+
+const NEW_PREFIX: &str = "new-";
+
+/// `.drafts` inside a journal directory. The builtin editor autosaves what is being written here,
+/// encrypted, so it can be recovered after a crash. A draft of an edit is named after its entry,
+/// and a draft of a new entry after when it was started, prefixed with `new-`.
+pub struct Drafts {
+    path: PathBuf,
+}
+
+impl Drafts {
+    pub fn new(directory: &Path) -> Self {
+        Drafts {
+            path: directory.join(".drafts"),
+        }
+    }
+
+    /// Name for the draft of a new entry started now.
+    pub fn new_name() -> String {
+        format!("{}{}", NEW_PREFIX, date::current_string())
+    }
+
+    /// When the new entry `draft` was started, in the format of entry names, or `None` for the
+    /// draft of an edit.
+    pub fn started(draft: &str) -> Option<&str> {
+        draft.strip_prefix(NEW_PREFIX)
+    }
+
+    /// The entry `draft` edits, or `None` for the draft of a new entry.
+    pub fn entry_of(draft: &str) -> Option<&str> {
+        match Drafts::started(draft) {
+            Some(_) => None,
+            None => Some(draft),
+        }
+    }
+
+    #[inline]
+    pub fn draft_path(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// Replace the draft `name` with `encrypted`. The old draft stays intact until the new one is
+    /// fully written.
+    pub fn write(&self, name: &str, encrypted: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.path)?;
+        let partial = self.path.join(format!(".{}", name));
+        fs::write(&partial, encrypted)?;
+        fs::rename(partial, self.draft_path(name))
+    }
+
+    /// Names of the drafts, oldest first.
+    pub fn names(&self) -> io::Result<Vec<String>> {
+        let read_dir = match fs::read_dir(&self.path) {
+            Ok(read_dir) => read_dir,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => return Err(error),
+        };
+        let mut names = vec![];
+        for draft in read_dir {
+            let name = draft?.file_name().to_string_lossy().to_string();
+            if !name.starts_with('.') {
+                names.push(name);
+            }
+        }
+        names.sort_by_cached_key(|name| date::parse(name.trim_start_matches(NEW_PREFIX)));
+        Ok(names)
+    }
+
+    /// Shred the draft `name`, if there is one.
+    pub fn remove(&self, name: &str) -> io::Result<()> {
+        match shred(&self.draft_path(name)) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::{fs, io, path::{Path, PathBuf}};
+use super::{date, shred};
+
+/// `.history` inside a journal directory. Before an entry is overwritten its ciphertext is copied
+/// to `.history/<entry>/<time of the edit>`, so revisions stay encrypted.
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(directory: &Path) -> Self {
+        History {
+            path: directory.join(".history"),
+        }
+    }
+
+    #[inline]
+    fn entry_dir(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    #[inline]
+    pub fn revision_path(&self, name: &str, revision: &str) -> PathBuf {
+        self.entry_dir(name).join(revision)
+    }
+
+    /// Keep a copy of the entry at `path` as its newest revision.
+    pub fn insert(&self, path: &Path) -> io::Result<()> {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let dir = self.entry_dir(&name);
+        fs::create_dir_all(&dir)?;
+        let now = date::current_string();
+        let mut revision = now.clone();
+        let mut n = 1;
+        while dir.join(&revision).exists() {
+            revision = format!("{}.{}", now, n);
+            n += 1;
+        }
+        fs::copy(path, dir.join(revision))?;
+        Ok(())
+    }
+
+    /// Revisions of the entry `name`, newest first.
+    pub fn revisions(&self, name: &str) -> io::Result<Vec<String>> {
+        let read_dir = match fs::read_dir(self.entry_dir(name)) {
+            Ok(read_dir) => read_dir,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => return Err(error),
+        };
+        let mut revisions = vec![];
+        for revision in read_dir {
+            revisions.push(revision?.file_name().to_string_lossy().to_string());
+        }
+        revisions.sort_by_key(|revision| {
+            let (date, n) = revision.split_once('.').unwrap_or((revision, "0"));
+            (date::newest_first(date), std::cmp::Reverse(n.parse::<usize>().unwrap_or(0)))
+        });
+        Ok(revisions)
+    }
+
+    /// Shred all but the newest `keep` revisions of the entry `name`.
+    pub fn prune(&self, name: &str, keep: usize) -> io::Result<()> {
+        for revision in self.revisions(name)?.into_iter().skip(keep) {
+            shred(&self.revision_path(name, &revision))?;
+        }
+        if keep == 0 {
+            let _ = fs::remove_dir(self.entry_dir(name));
+        }
+        Ok(())
+    }
+
+    /// Shred every revision of the entry `name`.
+    #[inline]
+    pub fn remove(&self, name: &str) -> io::Result<()> {
+        self.prune(name, 0)
+    }
+}
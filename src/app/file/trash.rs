@@ -0,0 +1,94 @@
+use super::shred;
+use std::{cmp::Reverse, fs, io, path::{Path, PathBuf}, time::{Duration, SystemTime}};
+
+/// `.trash` inside a journal directory. Deleted entries are moved here still encrypted, and their
+/// modification time is set to when they were deleted.
+pub struct Trash {
+    path: PathBuf,
+}
+
+pub struct TrashedEntry {
+    pub name: String,
+    pub deleted: SystemTime,
+}
+
+impl Trash {
+    pub fn new(directory: &Path) -> Self {
+        Trash {
+            path: directory.join(".trash"),
+        }
+    }
+
+    #[inline]
+    pub fn entry_path<S: AsRef<str>>(&self, name: S) -> PathBuf {
+        self.path.join(name.as_ref())
+    }
+
+    /// Move the entry at `path` into the trash. Fails with `AlreadyExists` when an entry of the
+    /// same name is trashed already.
+    pub fn insert(&self, path: &Path) -> io::Result<()> {
+        let name = path.file_name().ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} has no file name", path.display()),
+        ))?;
+        fs::create_dir_all(&self.path)?;
+        let trashed = self.path.join(name);
+        move_new(path, &trashed)?;
+        fs::File::options().write(true).open(&trashed)?.set_modified(SystemTime::now())
+    }
+
+    /// Move the trashed entry `name` back to `destination`. Fails with `AlreadyExists` when
+    /// `destination` exists.
+    pub fn restore<S: AsRef<str>>(&self, name: S, destination: &Path) -> io::Result<()> {
+        move_new(&self.entry_path(name), destination)
+    }
+
+    /// Trashed entries, most recently deleted first.
+    pub fn entries(&self) -> io::Result<Vec<TrashedEntry>> {
+        let mut entries = vec![];
+        let read_dir = match fs::read_dir(&self.path) {
+            Ok(read_dir) => read_dir,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(entries),
+            Err(error) => return Err(error),
+        };
+        for entry in read_dir {
+            let entry = entry?;
+            entries.push(TrashedEntry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                deleted: entry.metadata()?.modified()?,
+            });
+        }
+        entries.sort_by_key(|entry| Reverse(entry.deleted));
+        Ok(entries)
+    }
+
+    /// Shred entries deleted more than `max_age` ago. Returns their names.
+    pub fn purge(&self, max_age: Duration) -> io::Result<Vec<String>> {
+        let now = SystemTime::now();
+        let mut removed = vec![];
+        for entry in self.entries()? {
+            if now.duration_since(entry.deleted).unwrap_or_default() > max_age {
+                shred(&self.entry_path(&entry.name))?;
+                removed.push(entry.name);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Shred every trashed entry. Returns their names.
+    pub fn empty(&self) -> io::Result<Vec<String>> {
+        let entries = self.entries()?;
+        for entry in &entries {
+            shred(&self.entry_path(&entry.name))?;
+        }
+        Ok(entries.into_iter().map(|entry| entry.name).collect())
+    }
+}
+
+/// Rename `from` to `to`, unless that would replace an existing file.
+fn move_new(from: &Path, to: &Path) -> io::Result<()> {
+    if to.try_exists()? {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", to.display())))
+    }
+    fs::rename(from, to)
+}
@@ -0,0 +1,129 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// Inverted index from lowercase words to the names of the entries containing them.
+#[derive(Debug, Default)]
+pub struct Index {
+    words: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Lowercase alphanumeric words of `content`.
+pub fn tokenize(content: &str) -> BTreeSet<String> {
+    content.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+impl Index {
+    pub fn insert(&mut self, name: &str, content: &str) {
+        self.remove(name);
+        for word in tokenize(content) {
+            self.words.entry(word).or_default().insert(name.to_string());
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.words.retain(|_, names| {
+            names.remove(name);
+            !names.is_empty()
+        });
+    }
+
+    fn lookup(&self, term: &str, exact: bool) -> HashSet<String> {
+        if exact {
+            return self.words.get(term).into_iter().flatten().cloned().collect()
+        }
+        self.words.iter()
+            .filter(|(word, _)| word.contains(term))
+            .flat_map(|(_, names)| names.iter().cloned())
+            .collect()
+    }
+
+    /// Names of the entries containing every word of `query`. With `exact` words have to match
+    /// whole, otherwise any indexed word containing them counts. `None` when `query` has no words,
+    /// since every entry matches it, including ones without any indexed word.
+    pub fn candidates(&self, query: &str, exact: bool) -> Option<HashSet<String>> {
+        let mut candidates: Option<HashSet<String>> = None;
+        for term in tokenize(query) {
+            let names = self.lookup(&term, exact);
+            candidates = Some(match candidates {
+                Some(candidates) => candidates.intersection(&names).cloned().collect(),
+                None => names,
+            });
+        }
+        candidates
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut output = String::new();
+        for (word, names) in &self.words {
+            output.push_str(word);
+            output.push('\t');
+            output.push_str(&names.iter().cloned().collect::<Vec<_>>().join(" "));
+            output.push('\n');
+        }
+        output
+    }
+
+    pub fn deserialize(input: &str) -> Self {
+        let mut index = Index::default();
+        for line in input.lines() {
+            if let Some((word, names)) = line.split_once('\t') {
+                let names = names.split(' ').map(str::to_string).collect();
+                index.words.insert(word.to_string(), names);
+            }
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> Index {
+        let mut index = Index::default();
+        index.insert("a", "Went running, then WORK.");
+        index.insert("b", "work from home");
+        index.insert("c", "a quiet day");
+        index
+    }
+
+    fn names(candidates: Option<HashSet<String>>) -> BTreeSet<String> {
+        candidates.unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn candidates_match_every_word() {
+        let index = index();
+        assert_eq!(names(index.candidates("work", true)), BTreeSet::from(["a".into(), "b".into()]));
+        assert_eq!(names(index.candidates("Work home", true)), BTreeSet::from(["b".into()]));
+        assert!(names(index.candidates("work day", true)).is_empty());
+        assert!(names(index.candidates("run", true)).is_empty());
+        assert_eq!(names(index.candidates("run", false)), BTreeSet::from(["a".into()]));
+        assert_eq!(index.candidates("", true), None);
+        assert_eq!(index.candidates("...", false), None);
+    }
+
+    #[test]
+    fn insert_replaces_and_remove_forgets() {
+        let mut index = index();
+        index.insert("a", "a quiet evening");
+        assert_eq!(names(index.candidates("work", true)), BTreeSet::from(["b".into()]));
+        assert_eq!(names(index.candidates("quiet", true)), BTreeSet::from(["a".into(), "c".into()]));
+        index.remove("c");
+        assert_eq!(names(index.candidates("quiet", true)), BTreeSet::from(["a".into()]));
+        let index = Index::deserialize(&index.serialize());
+        assert_eq!(names(index.candidates("quiet", true)), BTreeSet::from(["a".into()]));
+        assert_eq!(index.candidates("", true), None);
+    }
+
+    #[test]
+    fn queries_without_words_match_empty_entries() {
+        let mut index = index();
+        index.insert("empty", "");
+        index.insert("dots", "...");
+        assert_eq!(index.candidates("?!", true), None);
+        assert!(!names(index.candidates("day", true)).contains("empty"));
+    }
+}
@@ -0,0 +1,100 @@
+use std::{collections::{HashMap, HashSet, VecDeque}, fs, path::PathBuf, thread};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use super::encryption::Encryption;
+use super::entry::Entry;
+use crate::locale::Locale;
+
+/// Decrypts entries for the TUI's preview pane on a background thread, so scrypt doesn't freeze
+/// the interface while moving through the list. Only what's asked for is decrypted, usually the
+/// selected entry and its neighbours for their summaries in the list.
+pub struct Previewer {
+    requests: Sender<Vec<(String, PathBuf)>>,
+    results: Receiver<(String, Result<Entry, String>)>,
+    /// Entries of the latest request that aren't decrypted yet
+    pending: HashSet<String>,
+    /// Entries that couldn't be decrypted, with why
+    failed: HashMap<String, String>,
+}
+
+impl Previewer {
+    pub fn new(encryption: Encryption, locale: Locale) -> Self {
+        let (requests, worker_requests) = mpsc::channel::<Vec<(String, PathBuf)>>();
+        let (worker_results, results) = mpsc::channel();
+        thread::spawn(move || {
+            let mut batch = VecDeque::new();
+            loop {
+                // Only the latest request matters when the selection moved on meanwhile
+                if let Some(newer) = worker_requests.try_iter().last() {
+                    batch = VecDeque::from(newer);
+                }
+                let Some((name, path)) = batch.pop_front() else {
+                    match worker_requests.recv() {
+                        Ok(newer) => batch = VecDeque::from(newer),
+                        Err(_) => return,
+                    }
+                    continue
+                };
+                let decrypted = decrypt(&encryption, &name, path, locale);
+                if worker_results.send((name, decrypted)).is_err() {
+                    return
+                }
+            }
+        });
+        Previewer {
+            requests,
+            results,
+            pending: HashSet::new(),
+            failed: HashMap::new(),
+        }
+    }
+
+    /// Ask for `entries` to be decrypted in order, instead of what was asked for before. Nothing
+    /// changes when they already are being decrypted.
+    pub fn request(&mut self, entries: Vec<(String, PathBuf)>) {
+        let entries: Vec<(String, PathBuf)> = entries.into_iter()
+            .filter(|(name, _)| !self.failed.contains_key(name))
+            .collect();
+        if entries.iter().all(|(name, _)| self.pending.contains(name)) {
+            return
+        }
+        let names = entries.iter().map(|(name, _)| name.clone()).collect();
+        if self.requests.send(entries).is_ok() {
+            self.pending = names;
+        }
+    }
+
+    /// Whether results are still to come.
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Why the entry `name` couldn't be decrypted, if it couldn't.
+    #[inline]
+    pub fn error(&self, name: &str) -> Option<&str> {
+        self.failed.get(name).map(String::as_str)
+    }
+
+    /// Entries decrypted since the last call.
+    pub fn receive(&mut self) -> Vec<(String, Entry)> {
+        let mut decrypted = vec![];
+        while let Ok((name, result)) = self.results.try_recv() {
+            self.pending.remove(&name);
+            match result {
+                Ok(entry) => decrypted.push((name, entry)),
+                Err(error) => {
+                    self.failed.insert(name, error);
+                }
+            }
+        }
+        decrypted
+    }
+}
+
+/// The entry `name` at `path`, with errors in `locale`.
+fn decrypt(encryption: &Encryption, name: &str, path: PathBuf, locale: Locale) -> Result<Entry, String> {
+    let encrypted = fs::read(path).map_err(|error| error.to_string())?;
+    let decrypted = encryption.decrypt(encrypted).map_err(|error| error.to_string())?;
+    Entry::deserialize(name, &decrypted, locale)
+}
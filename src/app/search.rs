@@ -0,0 +1,177 @@
+use regex::{Regex, RegexBuilder};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchOptions {
+    pub regex: bool,
+    pub ignore_case: bool,
+    pub word: bool,
+    pub fuzzy: bool,
+    pub context: usize,
+}
+
+pub enum Matcher {
+    Regex(Regex),
+    Fuzzy {
+        pattern: Vec<char>,
+        ignore_case: bool,
+    },
+}
+
+impl Matcher {
+    pub fn new<S: AsRef<str>>(pattern: S, options: &SearchOptions) -> Result<Self, regex::Error> {
+        let pattern = pattern.as_ref();
+        if options.fuzzy {
+            let pattern = if options.ignore_case {
+                pattern.to_lowercase()
+            } else {
+                pattern.to_string()
+            };
+            return Ok(Matcher::Fuzzy {
+                pattern: pattern.chars().filter(|c| !c.is_whitespace()).collect(),
+                ignore_case: options.ignore_case,
+            })
+        }
+        let mut pattern = if options.regex {
+            pattern.to_string()
+        } else {
+            regex::escape(pattern)
+        };
+        if options.word {
+            pattern = format!(r"\b(?:{pattern})\b");
+        }
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(options.ignore_case)
+            .build()?;
+        Ok(Matcher::Regex(regex))
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        match self {
+            Matcher::Regex(regex) => regex.is_match(line),
+            Matcher::Fuzzy { pattern, ignore_case } => {
+                let mut pattern = pattern.iter().peekable();
+                let chars: Box<dyn Iterator<Item = char>> = if *ignore_case {
+                    Box::new(line.chars().flat_map(char::to_lowercase))
+                } else {
+                    Box::new(line.chars())
+                };
+                for c in chars {
+                    if pattern.peek() == Some(&&c) {
+                        pattern.next();
+                    }
+                }
+                pattern.peek().is_none()
+            }
+        }
+    }
+}
+
+/// Matching lines of a single entry, with their surrounding context.
+pub struct SearchHit {
+    pub name: String,
+    /// `(line number, is a match, text)`, in order. A `None` marks a gap between context groups.
+    pub lines: Vec<Option<(usize, bool, String)>>,
+}
+
+impl SearchHit {
+    pub fn new(name: String, content: &str, matcher: &Matcher, context: usize) -> Option<Self> {
+        let lines: Vec<&str> = content.lines().collect();
+        let matched: Vec<usize> = lines.iter().enumerate()
+            .filter(|(_, line)| matcher.is_match(line))
+            .map(|(i, _)| i)
+            .collect();
+        if matched.is_empty() {
+            return None
+        }
+
+        let mut output = vec![];
+        let mut last: Option<usize> = None;
+        for &i in &matched {
+            let start = i.saturating_sub(context);
+            let end = (i + context).min(lines.len() - 1);
+            let start = match last {
+                Some(last) if start <= last + 1 => last + 1,
+                Some(_) => {
+                    output.push(None);
+                    start
+                }
+                None => start,
+            };
+            for (j, line) in lines.iter().enumerate().take(end + 1).skip(start) {
+                output.push(Some((j + 1, matched.contains(&j), line.to_string())));
+            }
+            last = Some(end.max(last.unwrap_or(0)));
+        }
+
+        Some(SearchHit {
+            name,
+            lines: output,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(pattern: &str, options: SearchOptions) -> Matcher {
+        Matcher::new(pattern, &options).unwrap()
+    }
+
+    #[test]
+    fn plain_patterns_are_literal() {
+        let matcher = matcher("a.c", SearchOptions::default());
+        assert!(matcher.is_match("xa.cx"));
+        assert!(!matcher.is_match("abc"));
+        assert!(!matcher.is_match("A.C"));
+    }
+
+    #[test]
+    fn regex_case_and_words() {
+        let options = SearchOptions { regex: true, ignore_case: true, ..Default::default() };
+        assert!(matcher("a.c", options).is_match("ABC"));
+        let options = SearchOptions { word: true, ..Default::default() };
+        let matcher = matcher("work", options);
+        assert!(matcher.is_match("at work today"));
+        assert!(!matcher.is_match("a workout"));
+        assert!(Matcher::new("(", &SearchOptions { regex: true, ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn fuzzy_matches_in_order() {
+        let options = SearchOptions { fuzzy: true, ..Default::default() };
+        assert!(matcher("hlo wd", options).is_match("hello world"));
+        assert!(!matcher("dlrow", options).is_match("hello world"));
+        assert!(!matcher("HW", options).is_match("hello world"));
+        let options = SearchOptions { fuzzy: true, ignore_case: true, ..Default::default() };
+        assert!(matcher("HW", options).is_match("hello world"));
+    }
+
+    #[test]
+    fn hits_group_context() {
+        let content = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight";
+        let matcher = matcher("o", SearchOptions::default());
+        let hit = SearchHit::new("name".to_string(), content, &matcher, 0).unwrap();
+        let lines: Vec<_> = hit.lines.iter()
+            .map(|line| line.as_ref().map(|(number, matched, _)| (*number, *matched)))
+            .collect();
+        assert_eq!(lines, [Some((1, true)), Some((2, true)), None, Some((4, true))]);
+
+        let matcher = self::matcher("three|seven", SearchOptions { regex: true, ..Default::default() });
+        let hit = SearchHit::new("name".to_string(), content, &matcher, 1).unwrap();
+        let lines: Vec<_> = hit.lines.iter()
+            .map(|line| line.as_ref().map(|(number, matched, _)| (*number, *matched)))
+            .collect();
+        assert_eq!(lines, [
+            Some((2, false)), Some((3, true)), Some((4, false)),
+            None,
+            Some((6, false)), Some((7, true)), Some((8, false)),
+        ]);
+        let hit = SearchHit::new("name".to_string(), content, &matcher, 3).unwrap();
+        assert_eq!(hit.lines.len(), 8);
+        assert!(hit.lines.iter().all(Option::is_some));
+        let hit = SearchHit::new("name".to_string(), content, &matcher, 0).unwrap();
+        assert_eq!(hit.lines[0], Some((3, true, "three".to_string())));
+        assert!(SearchHit::new("name".to_string(), "nothing", &matcher, 1).is_none());
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::{BTreeMap, BTreeSet};
+use chrono::{DateTime, Datelike, Days, FixedOffset, Local, NaiveDate, SecondsFormat, Timelike};
+use serde::{Deserialize, Serialize};
+
+use super::file::date::Calendar;
+
+/// What statistics need of an entry, kept in the encrypted stats cache so they don't need every
+/// entry decrypted again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryStats {
+    /// Size and modification time of the entry's file when this was computed
+    pub stamp: String,
+    pub created: DateTime<FixedOffset>,
+    pub words: usize,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, f64>,
+}
+
+/// Stats of every entry by name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StatsCache {
+    pub entries: BTreeMap<String, EntryStats>,
+}
+
+impl StatsCache {
+    pub fn serialize(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    /// An empty cache when `input` isn't one, so it's rebuilt.
+    pub fn deserialize(input: &str) -> Self {
+        toml::from_str(input).unwrap_or_default()
+    }
+}
+
+/// How much and when entries were written.
+#[derive(Debug, Default)]
+pub struct Writing {
+    pub entries: usize,
+    pub words: usize,
+    /// Most days in a row with entries, and the days in a row up to today (or yesterday, while
+    /// today can still continue it)
+    pub longest_streak: usize,
+    pub current_streak: usize,
+    /// Entries written on each weekday, Monday first
+    pub weekdays: [usize; 7],
+    /// Entries written in each hour of the day
+    pub hours: [usize; 24],
+    /// Entries and words of each month with entries, by the first day of the month in the
+    /// calendar
+    pub months: BTreeMap<NaiveDate, (usize, usize)>,
+}
+
+impl Writing {
+    pub fn new(entries: &[EntryStats], calendar: Calendar, today: NaiveDate) -> Self {
+        let mut writing = Writing {
+            entries: entries.len(),
+            ..Writing::default()
+        };
+        let mut days = BTreeSet::new();
+        for entry in entries {
+            let created = entry.created.with_timezone(&Local);
+            let day = created.date_naive();
+            writing.words += entry.words;
+            writing.weekdays[day.weekday().num_days_from_monday() as usize] += 1;
+            writing.hours[created.hour() as usize] += 1;
+            let month = writing.months.entry(calendar.first_of_month(day)).or_default();
+            month.0 += 1;
+            month.1 += entry.words;
+            days.insert(day);
+        }
+        let mut streak = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for &day in &days {
+            streak = if previous.and_then(|previous| previous.succ_opt()) == Some(day) { streak + 1 } else { 1 };
+            writing.longest_streak = writing.longest_streak.max(streak);
+            previous = Some(day);
+        }
+        let mut day = if days.contains(&today) { Some(today) } else { today.pred_opt() };
+        while let Some(current) = day.filter(|day| days.contains(day)) {
+            writing.current_streak += 1;
+            day = current.checked_sub_days(Days::new(1));
+        }
+        writing
+    }
+
+    /// Average words per entry.
+    #[inline]
+    pub fn average(&self) -> usize {
+        self.words.checked_div(self.entries).unwrap_or(0)
+    }
+}
+
+/// Numeric front-matter fields of entries, like mood or hours slept, over time.
+#[derive(Debug, Default)]
+pub struct Series {
+    /// Names of the fields, the configured ones first
+    pub fields: Vec<String>,
+    /// When each entry with fields was written, and its fields, oldest first
+    pub points: Vec<(DateTime<FixedOffset>, BTreeMap<String, f64>)>,
+}
+
+/// How a field's values are spread.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldSummary {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub last: f64,
+}
+
+impl Series {
+    /// The series of `points`, with the `configured` fields first and then the others found in
+    /// them, alphabetically.
+    pub fn new(configured: &[String], mut points: Vec<(DateTime<FixedOffset>, BTreeMap<String, f64>)>) -> Self {
+        points.retain(|(_, values)| !values.is_empty());
+        points.sort_by_key(|(created, _)| *created);
+        let mut fields = configured.to_vec();
+        for (_, values) in &points {
+            for name in values.keys() {
+                if !fields.contains(name) {
+                    fields.push(name.clone());
+                }
+            }
+        }
+        fields[configured.len()..].sort();
+        Series { fields, points }
+    }
+
+    /// The values of `field`, oldest first.
+    pub fn values(&self, field: &str) -> Vec<(DateTime<FixedOffset>, f64)> {
+        self.points.iter()
+            .filter_map(|(created, values)| Some((*created, *values.get(field)?)))
+            .collect()
+    }
+
+    /// `None` for fields without values.
+    pub fn summary(&self, field: &str) -> Option<FieldSummary> {
+        let values: Vec<f64> = self.values(field).into_iter().map(|(_, value)| value).collect();
+        let last = *values.last()?;
+        Some(FieldSummary {
+            count: values.len(),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            last,
+        })
+    }
+
+    /// The values of `fields` as CSV, one row per entry that has any of them, with the local time
+    /// it was written first.
+    pub fn to_csv(&self, fields: &[String]) -> String {
+        let mut csv = String::from("date");
+        for field in fields {
+            csv.push(',');
+            csv.push_str(&csv_field(field));
+        }
+        csv.push('\n');
+        for (created, values) in &self.points {
+            if !fields.iter().any(|field| values.contains_key(field)) {
+                continue
+            }
+            csv.push_str(&created.with_timezone(&Local).to_rfc3339_opts(SecondsFormat::Secs, false));
+            for field in fields {
+                csv.push(',');
+                if let Some(value) = values.get(field) {
+                    csv.push_str(&value.to_string());
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// `value` quoted when it has characters CSV gives a meaning to.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(day: u32, values: &[(&str, f64)]) -> (DateTime<FixedOffset>, BTreeMap<String, f64>) {
+        let created = DateTime::parse_from_rfc3339(&format!("2024-03-{:02}T12:00:00+00:00", day)).unwrap();
+        (created, values.iter().map(|(name, value)| (name.to_string(), *value)).collect())
+    }
+
+    fn written(day: u32, words: usize) -> EntryStats {
+        let naive = NaiveDate::from_ymd_opt(2024, 3, day).unwrap().and_hms_opt(21, 0, 0).unwrap();
+        EntryStats {
+            stamp: String::new(),
+            created: naive.and_local_timezone(Local).unwrap().fixed_offset(),
+            words,
+            values: BTreeMap::new(),
+        }
+    }
+
+    fn march(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn streaks() {
+        let entries: Vec<EntryStats> = [1, 2, 2, 3, 5, 6].into_iter().map(|day| written(day, 10)).collect();
+        let streaks = |today| {
+            let writing = Writing::new(&entries, Calendar::Gregorian, today);
+            (writing.longest_streak, writing.current_streak)
+        };
+        assert_eq!(streaks(march(6)), (3, 2));
+        assert_eq!(streaks(march(7)), (3, 2));
+        assert_eq!(streaks(march(8)), (3, 0));
+        assert_eq!(streaks(march(4)), (3, 3));
+
+        let writing = Writing::new(&[], Calendar::Gregorian, march(6));
+        assert_eq!((writing.longest_streak, writing.current_streak, writing.average()), (0, 0, 0));
+    }
+
+    #[test]
+    fn writing_totals() {
+        let entries = [written(4, 100), written(5, 50), written(20, 30), written(21, 20)];
+        let writing = Writing::new(&entries, Calendar::Jalali, march(21));
+        assert_eq!((writing.entries, writing.words, writing.average()), (4, 200, 50));
+        assert_eq!(writing.weekdays, [1, 1, 1, 1, 0, 0, 0]);
+        assert_eq!(writing.hours[21], 4);
+        let months: Vec<_> = writing.months.into_iter().collect();
+        assert_eq!(months, [(NaiveDate::from_ymd_opt(2024, 2, 20).unwrap(), (2, 150)), (march(20), (2, 50))]);
+    }
+
+    #[test]
+    fn series_fields_and_values() {
+        let series = Series::new(&["mood".to_string(), "sleep".to_string()], vec![
+            point(3, &[("mood", 4.0), ("weight", 70.0)]),
+            point(1, &[("mood", 8.0), ("coffee", 2.0)]),
+            point(2, &[]),
+            point(4, &[("mood", 6.0)]),
+        ]);
+        assert_eq!(series.fields, ["mood", "sleep", "coffee", "weight"]);
+        assert_eq!(series.points.len(), 3);
+        let moods: Vec<f64> = series.values("mood").into_iter().map(|(_, value)| value).collect();
+        assert_eq!(moods, [8.0, 4.0, 6.0]);
+        assert!(series.values("sleep").is_empty());
+    }
+
+    #[test]
+    fn series_summary_and_csv() {
+        let series = Series::new(&[], vec![
+            point(1, &[("mood", 8.0)]),
+            point(2, &[("mood", 4.0), ("notes, short", 1.0)]),
+            point(3, &[("mood", 6.0)]),
+        ]);
+        let summary = series.summary("mood").unwrap();
+        assert_eq!((summary.count, summary.min, summary.max, summary.mean, summary.last), (3, 4.0, 8.0, 6.0, 6.0));
+        assert!(series.summary("sleep").is_none());
+
+        let csv = series.to_csv(&["notes, short".to_string()]);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("date,\"notes, short\""));
+        assert!(lines.next().is_some_and(|line| line.ends_with(",1")));
+        assert_eq!(lines.next(), None);
+    }
+}
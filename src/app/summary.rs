@@ -0,0 +1,279 @@
+use std::{collections::BTreeMap, ops::Range};
+
+/// What the journal list shows of an entry besides its date.
+pub struct Summary {
+    pub title: String,
+    pub words: usize,
+    pub tags: Vec<String>,
+    /// Front-matter fields with numbers, like `mood: 7`
+    pub values: BTreeMap<String, f64>,
+}
+
+impl Summary {
+    /// Summarize `content`. The title is the `title` field of a leading `---` front-matter block,
+    /// or else the first non-empty line. Tags are the front-matter `tags` and `#hashtags` in the
+    /// text.
+    pub fn new(content: &str) -> Self {
+        let (front_matter, body) = split_front_matter(content);
+        let mut title = None;
+        let mut tags = vec![];
+        let mut values = BTreeMap::new();
+        for (key, value) in fields(front_matter) {
+            match key {
+                "title" if !value.is_empty() => title = Some(value.to_string()),
+                "tags" => tags.extend(parse_tags(value)),
+                key => {
+                    if let Some(number) = value.parse::<f64>().ok().filter(|number| number.is_finite()) {
+                        values.insert(key.to_string(), number);
+                    }
+                }
+            }
+        }
+        for tag in hashtags(body) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        let title = title.unwrap_or_else(|| {
+            body.lines()
+                .map(|line| line.trim().trim_start_matches('#').trim())
+                .find(|line| !line.is_empty())
+                .unwrap_or_default()
+                .to_string()
+        });
+        Summary {
+            title,
+            words: body.split_whitespace().count(),
+            tags,
+            values,
+        }
+    }
+}
+
+/// Split `content` into its `---` delimited front-matter, which is empty when there's none, and
+/// the rest. The closing line is `---` alone, so lines like `----` don't end it.
+pub fn split_front_matter(content: &str) -> (&str, &str) {
+    let Some(rest) = content.strip_prefix("---\n") else {
+        return ("", content)
+    };
+    let mut start = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let front_matter = &rest[..start];
+            return (front_matter.strip_suffix('\n').unwrap_or(front_matter), &rest[start + line.len()..])
+        }
+        start += line.len();
+    }
+    ("", content)
+}
+
+/// `key: value` lines of a front-matter block, trimmed and unquoted. Lines without a key are
+/// skipped.
+pub fn fields(front_matter: &str) -> impl Iterator<Item = (&str, &str)> {
+    field_lines(front_matter).into_iter().map(|(_, key, value)| (key, value))
+}
+
+/// The fields of a front-matter block with the bytes of the lines each takes. A key with no value
+/// on its line takes the `- item` lines of a YAML list following it as its value.
+fn field_lines(front_matter: &str) -> Vec<(Range<usize>, &str, &str)> {
+    let mut lines = vec![];
+    let mut start = 0;
+    for line in front_matter.split_inclusive('\n') {
+        lines.push(start..start + line.len());
+        start += line.len();
+    }
+    let is_item = |line: &Range<usize>| front_matter[line.clone()].trim_start().starts_with("- ");
+    let mut fields = vec![];
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].clone();
+        i += 1;
+        let Some((key, value)) = front_matter[line.clone()].split_once(':') else {
+            continue
+        };
+        let value = value.trim();
+        if !value.is_empty() {
+            fields.push((line, key.trim(), value.trim_matches('"')));
+            continue
+        }
+        let items = i;
+        while i < lines.len() && is_item(&lines[i]) {
+            i += 1;
+        }
+        let end = lines.get(i).map_or(front_matter.len(), |line| line.start);
+        let list = lines.get(items).map_or(end, |line| line.start);
+        fields.push((line.start..end, key.trim(), front_matter[list..end].trim()));
+    }
+    fields
+}
+
+/// `content` with the front-matter field `key` set to `value`, adding the front-matter when it
+/// has none. The field is replaced where it is, and everything else is kept as written.
+pub fn set_field(content: &str, key: &str, value: &str) -> String {
+    let field = format!("{}: {}", key, value);
+    let (front_matter, body) = split_front_matter(content);
+    if front_matter.len() + body.len() == content.len() {
+        return format!("---\n{}\n---\n{}", field, body)
+    }
+    // The front-matter follows its opening `---` line
+    let start = "---\n".len();
+    let end = start + front_matter.len();
+    let mut replaced = false;
+    let mut result = content[..start].to_string();
+    let mut written = 0;
+    for (lines, other, _) in field_lines(front_matter) {
+        if other != key {
+            continue
+        }
+        result.push_str(&front_matter[written..lines.start]);
+        // Only the first of duplicated fields is kept
+        if !replaced {
+            result.push_str(&field);
+            if front_matter[lines.clone()].ends_with('\n') {
+                result.push('\n');
+            }
+            replaced = true;
+        } else if !front_matter[lines.clone()].ends_with('\n') {
+            // A dropped last line takes the line break before it along
+            result.pop();
+        }
+        written = lines.end;
+    }
+    result.push_str(&front_matter[written..]);
+    if !replaced {
+        if !front_matter.is_empty() {
+            result.push('\n');
+        }
+        result.push_str(&field);
+        if front_matter.is_empty() {
+            result.push('\n');
+        }
+    }
+    result.push_str(&content[end..]);
+    result
+}
+
+/// Tags written like `[work, "travel"]`, `work, travel`, `#work #travel` or as a YAML list.
+pub fn parse_tags(value: &str) -> Vec<String> {
+    value.trim_start_matches('[').trim_end_matches(']')
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(|tag| tag.trim().trim_matches('"').trim_start_matches('#').to_string())
+        .filter(|tag| !tag.is_empty() && tag != "-")
+        .collect()
+}
+
+/// `#words` in `text`, without the `#`. Markdown headings (`# Heading`) aren't tags.
+pub fn hashtags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        let Some(tag) = word.strip_prefix('#') else {
+            continue
+        };
+        let tag: String = tag.chars().take_while(|c| is_tag_char(*c)).collect();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// `text` with its `#old` hashtags written as `#new`.
+pub fn rename_hashtag(text: &str, old: &str, new: &str) -> String {
+    let mut renamed = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut word_start = true;
+    while let Some(c) = rest.chars().next() {
+        if c == '#' && word_start {
+            let after = &rest[1..];
+            let len = after.find(|c| !is_tag_char(c)).unwrap_or(after.len());
+            if &after[..len] == old {
+                renamed.push('#');
+                renamed.push_str(new);
+                rest = &after[len..];
+                word_start = false;
+                continue
+            }
+        }
+        renamed.push(c);
+        rest = &rest[c.len_utf8()..];
+        word_start = c.is_whitespace();
+    }
+    renamed
+}
+
+#[inline]
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Whether `tag` can be written as a `#hashtag`.
+pub fn is_tag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(is_tag_char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn front_matter() {
+        assert_eq!(split_front_matter("---\ntitle: a\n---\nbody"), ("title: a", "body"));
+        assert_eq!(split_front_matter("---\ntitle: a\n--- \nbody\n"), ("title: a", "body\n"));
+        assert_eq!(split_front_matter("---\ntitle: a\n---"), ("title: a", ""));
+        assert_eq!(split_front_matter("---\n---\nbody"), ("", "body"));
+        assert_eq!(split_front_matter("no front-matter\n---\n"), ("", "no front-matter\n---\n"));
+        assert_eq!(split_front_matter("---\nunclosed"), ("", "---\nunclosed"));
+    }
+
+    #[test]
+    fn rules_inside_front_matter() {
+        let content = "---\ntitle: a\n----\n---foo\nmood: 3\n---\nbody";
+        assert_eq!(split_front_matter(content), ("title: a\n----\n---foo\nmood: 3", "body"));
+    }
+
+    #[test]
+    fn hashtag_words() {
+        assert_eq!(hashtags("#work and #workout, #work again"), ["work", "workout"]);
+        assert_eq!(hashtags("# Heading\nending with #tag."), ["tag"]);
+        assert_eq!(hashtags("#a-b_c #x#y"), ["a-b_c", "x"]);
+        assert!(hashtags("https://example.com/#work mail#work (#work)").is_empty());
+    }
+
+    #[test]
+    fn tag_names() {
+        assert!(is_tag("work-life_2"));
+        assert!(is_tag("سفر"));
+        for tag in ["", "two words", "a#b", "a.b", "#work"] {
+            assert!(!is_tag(tag), "{tag:?}");
+        }
+    }
+
+    #[test]
+    fn rename_whole_hashtags() {
+        assert_eq!(rename_hashtag("#work #workout #work-life", "work", "job"), "#job #workout #work-life");
+        assert_eq!(rename_hashtag("at the end #work", "work", "job"), "at the end #job");
+        assert_eq!(rename_hashtag("#work, then #work.", "work", "job"), "#job, then #job.");
+        assert_eq!(rename_hashtag("#work\n#work", "work", "job"), "#job\n#job");
+        let untouched = "https://example.com/#work mail#work (#work) #Work";
+        assert_eq!(rename_hashtag(untouched, "work", "job"), untouched);
+    }
+
+    #[test]
+    fn front_matter_fields() {
+        let front_matter = "title: \"A day\"\nno colon here\ntags:\n  - work\n  - home\nmood: 7";
+        let fields: Vec<(&str, &str)> = fields(front_matter).collect();
+        assert_eq!(fields, [("title", "A day"), ("tags", "- work\n  - home"), ("mood", "7")]);
+        assert_eq!(parse_tags(fields[1].1), ["work", "home"]);
+        assert_eq!(Summary::new("---\ntags:\n- work\n---\n#home").tags, ["work", "home"]);
+    }
+
+    #[test]
+    fn set_fields_in_place() {
+        let content = "---\nmood: 3\nno colon\ntitle: a\nmood: 4\n---\nbody";
+        assert_eq!(set_field(content, "mood", "5"), "---\nmood: 5\nno colon\ntitle: a\n---\nbody");
+        assert_eq!(set_field(content, "sleep", "8"), "---\nmood: 3\nno colon\ntitle: a\nmood: 4\nsleep: 8\n---\nbody");
+        assert_eq!(set_field("---\ntags:\n  - a\n---\nbody", "tags", "[b]"), "---\ntags: [b]\n---\nbody");
+        assert_eq!(set_field("---\n---\nbody", "mood", "5"), "---\nmood: 5\n---\nbody");
+        assert_eq!(set_field("body", "mood", "5"), "---\nmood: 5\n---\nbody");
+    }
+}
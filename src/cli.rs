@@ -1,6 +1,15 @@
 use std::io;
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Days, Local, NaiveDate};
+use similar::TextDiff;
+use unicode_width::UnicodeWidthStr;
 
-use crate::app::App;
+use crate::app::{App, AppError};
+use crate::app::encryption;
+use crate::app::file::date;
+use crate::app::search::SearchOptions;
+use crate::locale::{Locale, Message};
+use crate::{Command, TagsCommand, TrashCommand};
 
 fn getline<S: AsRef<str>>(prompt: S) -> io::Result<String>{
     println!("{}", prompt.as_ref());
@@ -10,13 +19,266 @@ fn getline<S: AsRef<str>>(prompt: S) -> io::Result<String>{
     Ok(output.lines().next().unwrap().to_string())
 }
 
-pub fn run(app: &mut App) -> io::Result<()>{
-    if app.no_passphrase() {
-        app.set_passphrase(getline("Enter your password")?);
+fn search(app: &mut App, pattern: String, options: SearchOptions) -> io::Result<()> {
+    let hits = match app.search(&pattern, &options) {
+        Ok(hits) => hits,
+        Err(error) => {
+            println!("{}", app.locale().format(Message::Error, &[&error]));
+            return Ok(());
+        }
+    };
+    for (i, hit) in hits.into_iter().enumerate() {
+        if i > 0 && options.context > 0 {
+            println!("--");
+        }
+        for line in hit.lines {
+            match line {
+                Some((number, true, text)) => println!("{}:{}:{}", hit.name, number, text),
+                Some((number, false, text)) => println!("{}-{}-{}", hit.name, number, text),
+                None => println!("--"),
+            }
+        }
     }
+    Ok(())
+}
+
+fn bench_kdf(target: Duration, locale: Locale) {
+    let mut recommended = None;
+    for log_n in 10..64 {
+        let Ok(time) = encryption::unlock_time(log_n) else {
+            break;
+        };
+        println!("{}", locale.format(Message::KdfTime, &[&format!("{:2}", log_n), &format!("{:>8.1?}", time)]));
+        if time <= target {
+            recommended = Some(log_n);
+        }
+        if time > target * 2 {
+            break;
+        }
+    }
+    match recommended {
+        Some(log_n) => {
+            println!("\n{}\n\n[security]\nwork_factor = {}", locale.format(Message::KdfRecommended, &[&format!("{:?}", target)]), log_n);
+        }
+        None => println!("\n{}", locale.format(Message::KdfTooSlow, &[&format!("{:?}", target)])),
+    }
+}
+
+fn trash(app: &mut App, command: TrashCommand) -> io::Result<()> {
+    match command {
+        TrashCommand::List => {
+            let kept = Duration::from_secs(app.config().trash_days * 24 * 60 * 60);
+            for entry in app.trash().entries()? {
+                let deleted: DateTime<Local> = entry.deleted.into();
+                let left = (entry.deleted + kept).duration_since(SystemTime::now()).unwrap_or_default();
+                let deleted = app.config().date_style().format(&deleted, &app.config().date_format);
+                println!("{}", app.locale().format(Message::TrashEntry, &[&entry.name, &deleted, &(left.as_secs() / (24 * 60 * 60))]));
+            }
+        }
+        TrashCommand::Restore { ids } => {
+            for id in ids {
+                if let Err(error) = app.restore(&id) {
+                    println!("{}", app.locale().format(Message::Error, &[&format!("{}: {}", id, error)]));
+                }
+            }
+        }
+        TrashCommand::Empty => {
+            let count = app.empty_trash()?;
+            println!("{}", app.locale().format(Message::RemovedEntries, &[&count]));
+        }
+    }
+    Ok(())
+}
 
-    if app.test_passphrase().is_err() {
-        println!("Error: Incorrect password");
+fn tags(app: &mut App, command: Option<TagsCommand>) -> io::Result<()> {
+    match command {
+        Some(TagsCommand::Rename { old, new }) => {
+            let (old, new) = (old.trim_start_matches('#'), new.trim_start_matches('#'));
+            let count = app.rename_tag(old, new)?;
+            println!("{}", app.locale().format(Message::RenamedTag, &[&old, &new, &count]));
+        }
+        None => {
+            for (tag, count) in app.tags() {
+                println!("{}\t{}", tag, count);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn stats(app: &mut App, fields: Vec<String>, csv: bool) -> io::Result<()> {
+    let series = app.series()?;
+    if let Some(unknown) = fields.iter().find(|field| !series.fields.contains(field)) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, app.locale().format(Message::UnknownField, &[unknown])))
+    }
+    if csv {
+        let fields = if fields.is_empty() { series.fields.clone() } else { fields };
+        print!("{}", series.to_csv(&fields));
+        return Ok(())
+    }
+    let fields = if fields.is_empty() {
+        writing(app)?;
+        if !series.fields.is_empty() {
+            println!();
+        }
+        series.fields.clone()
+    } else {
+        fields
+    };
+    for field in fields {
+        if let Some(summary) = series.summary(&field) {
+            let mean = format!("{:.1}", summary.mean);
+            println!("{}", app.locale().format(Message::FieldSummary,
+                &[&field, &summary.count, &summary.min, &mean, &summary.max, &summary.last]));
+        }
+    }
+    Ok(())
+}
+
+/// Print the totals and streaks of the entries, and bars of when they were written.
+fn writing(app: &mut App) -> io::Result<()> {
+    let writing = app.writing()?;
+    let locale = app.locale();
+    let style = app.config().date_style();
+    println!("{}", locale.format(Message::TotalEntries, &[&writing.entries]));
+    println!("{}", locale.format(Message::TotalWords, &[&writing.words]));
+    println!("{}", locale.format(Message::AverageLength, &[&writing.average()]));
+    println!("{}", locale.format(Message::LongestStreak, &[&writing.longest_streak]));
+    println!("{}", locale.format(Message::CurrentStreak, &[&writing.current_streak]));
+    let monday = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    let weekdays = writing.weekdays.iter().enumerate()
+        .map(|(i, count)| (style.format_day(monday + Days::new(i as u64), "%a"), *count, String::new()));
+    print_bars(locale.text(Message::ByWeekday), weekdays);
+    let hours = writing.hours.iter().enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(hour, count)| (style.digits(&format!("{:02}", hour)), *count, String::new()));
+    print_bars(locale.text(Message::ByHour), hours);
+    let months = writing.months.iter()
+        .map(|(first, (entries, words))| (style.format_day(*first, "%b %Y"), *entries, locale.format(Message::Words, &[words])));
+    print_bars(locale.text(Message::ByMonth), months);
+    Ok(())
+}
+
+/// A bar for each `(label, count, note)`, as long as the count relative to the largest.
+fn print_bars(title: &str, bars: impl Iterator<Item = (String, usize, String)>) {
+    const WIDTH: usize = 30;
+    let bars: Vec<(String, usize, String)> = bars.collect();
+    let max = bars.iter().map(|(_, count, _)| *count).max().unwrap_or(0).max(1);
+    let label_width = bars.iter().map(|(label, _, _)| label.width()).max().unwrap_or(0);
+    println!("\n{}", title);
+    for (label, count, note) in bars {
+        let bar = "█".repeat((count * WIDTH).div_ceil(max));
+        let padding = " ".repeat(label_width - label.width());
+        let line = format!("{}{}  {} {} {}", label, padding, bar, count, note);
+        println!("{}", line.trim_end());
+    }
+}
+
+fn on_this_day(app: &mut App) -> io::Result<()> {
+    app.set_on_this_day(true)?;
+    let entries = app.entries();
+    if entries.is_empty() {
+        println!("{}", app.locale().text(Message::NothingOnThisDay));
+        return Ok(())
+    }
+    let style = app.config().date_style();
+    let this_year = style.calendar.ymd(date::current().date_naive()).0;
+    let mut last_year = None;
+    for name in entries {
+        let year = date::parse(&name).map(|created| style.calendar.ymd(created.date_naive()).0);
+        if let Some(year) = year.filter(|year| last_year != Some(*year)) {
+            if last_year.is_some() {
+                println!();
+            }
+            println!("== {} ==", date::years_ago(app.locale(), style, year, this_year));
+            last_year = Some(year);
+        }
+        println!("\n{}", date::display(&name, &app.config().date_format, style));
+        match app.content(&name) {
+            Ok(content) => println!("{}", content),
+            Err(error) => println!("{}", app.locale().format(Message::Error, &[&format!("{}: {}", name, error)])),
+        }
+    }
+    Ok(())
+}
+
+fn history(app: &mut App, id: String, show: Option<String>, diff: Option<String>, restore: Option<String>) -> io::Result<()> {
+    if let Some(revision) = show {
+        print!("{}", app.revision_content(&id, &revision)?);
+    } else if let Some(revision) = diff {
+        let old = app.revision_content(&id, &revision)?;
+        let new = app.content(&id)?;
+        let diff = TextDiff::from_lines(&old, &new);
+        print!("{}", diff.unified_diff().header(&revision, &id));
+    } else if let Some(revision) = restore {
+        app.restore_revision(&id, &revision)?;
+    } else {
+        for revision in app.revisions(&id)? {
+            println!("{}", revision);
+        }
+    }
+    Ok(())
+}
+
+pub fn run(app: &mut App, command: Option<Command>) -> io::Result<()>{
+    // Neither reads an entry, so they don't ask for the passphrase
+    let locked = matches!(command, Some(Command::BenchKdf { .. } | Command::Config));
+    if !locked {
+        if app.no_passphrase() {
+            app.set_passphrase(getline(app.locale().text(Message::EnterPassphrase))?)?;
+        }
+
+        match app.test_passphrase() {
+            Err(AppError::ExcessiveWork(required)) => {
+                println!("{}", app.locale().format(Message::ExcessiveWork, &[&required]));
+                return Ok(());
+            }
+            Err(_) => {
+                println!("{}", app.locale().text(Message::IncorrectPassphrase));
+                return Ok(());
+            }
+            Ok(()) => {}
+        }
+    }
+    if let Some(command) = command {
+        match command {
+            Command::Search { pattern, regex, ignore_case, word, fuzzy, context } => {
+                let options = SearchOptions { regex, ignore_case, word, fuzzy, context };
+                search(app, pattern, options)?;
+            }
+            Command::Reindex => {
+                let count = app.reindex()?;
+                println!("{}", app.locale().format(Message::IndexedEntries, &[&count]));
+            }
+            Command::Migrate => {
+                let migrated = app.migrate()?;
+                for name in &migrated {
+                    println!("{}", name);
+                }
+                println!("{}", app.locale().format(Message::MigratedEntries, &[&migrated.len()]));
+            }
+            Command::Delete { ids, shred } => {
+                let entries = app.entries();
+                for id in ids {
+                    if !entries.contains(&id) {
+                        println!("{}", app.locale().format(Message::NoEntry, &[&id]));
+                        continue;
+                    }
+                    app.delete(&id, shred)?;
+                }
+            }
+            Command::History { id, show, diff, restore } => {
+                if let Err(error) = history(app, id, show, diff, restore) {
+                    println!("{}", app.locale().format(Message::Error, &[&error]));
+                }
+            }
+            Command::Tags { command } => tags(app, command)?,
+            Command::Stats { fields, csv } => stats(app, fields, csv)?,
+            Command::OnThisDay => on_this_day(app)?,
+            Command::Trash { command } => trash(app, command)?,
+            Command::Config => print!("{}", app.config().to_toml()),
+            Command::BenchKdf { target_ms } => bench_kdf(Duration::from_millis(target_ms), app.locale()),
+        }
         return Ok(());
     }
     app.read()?;
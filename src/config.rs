@@ -0,0 +1,743 @@
+use std::{env, fmt, fs, io, path::PathBuf};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use tui_textarea::{Input, Key};
+
+use crate::append_home_dir;
+use crate::app::entry::{parse_mood, MOOD};
+use crate::app::file::date::{Calendar, DateStyle};
+use crate::locale::{Locale, Message};
+
+/// `$XDG_CONFIG_HOME/jou/config.toml`, falling back to `~/.config/jou/config.toml`
+pub fn default_path() -> PathBuf {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(config_home) if !config_home.is_empty() => PathBuf::from(config_home).join("jou").join("config.toml"),
+        _ => append_home_dir([".config", "jou", "config.toml"]),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Journal directory used when `--path` isn't passed
+    pub path: PathBuf,
+    /// How entry dates are displayed, in strftime format
+    pub date_format: String,
+    /// Calendar of displayed dates: `gregorian`, or `jalali` for Solar Hijri dates with Persian
+    /// month names
+    pub calendar: Calendar,
+    /// Write the digits of displayed dates as Persian digits
+    pub persian_digits: bool,
+    /// Language of the interface: `en` or `fa`. Taken from `LANG` when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<Locale>,
+    /// Whether entries are listed newest written or newest edited first
+    pub sort: Sort,
+    /// External editor command for writing entries, split into words like a shell would. The
+    /// builtin editor is used when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// Days deleted entries are kept in the trash. Entries are deleted right away when 0
+    pub trash_days: u64,
+    /// Revisions kept of each edited entry. Edits overwrite entries without a trace when 0
+    pub history_keep: usize,
+    /// Seconds between autosaves of the builtin editor's encrypted draft. No drafts are kept when 0
+    pub autosave_secs: u64,
+    /// Numbers recorded in the front-matter of entries, like mood or hours slept, that the builtin
+    /// editor asks for when saving an entry without them. Entries can't be saved with values
+    /// outside their range
+    pub fields: Vec<Field>,
+    pub theme: Theme,
+    pub panes: Panes,
+    pub keys: Keys,
+    pub security: Security,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            path: crate::default_directory(),
+            date_format: String::from("%a %-d %b %Y, %H:%M"),
+            calendar: Calendar::Gregorian,
+            persian_digits: false,
+            locale: None,
+            sort: Sort::Created,
+            editor: None,
+            trash_days: 30,
+            history_keep: 10,
+            autosave_secs: 5,
+            fields: vec![],
+            theme: Theme::default(),
+            panes: Panes::default(),
+            keys: Keys::default(),
+            security: Security::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Read the config at `path`. A missing file gives the defaults.
+    pub fn load(path: &PathBuf) -> io::Result<Self> {
+        let invalid = |error: &dyn fmt::Display| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), error));
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(error) => return Err(error),
+        };
+        let table: toml::Table = toml::from_str(&content).map_err(|error| invalid(&error))?;
+        let locale = table.get("locale").and_then(|locale| locale.clone().try_into().ok());
+        check_keys(&table, Locale::detect(locale)).map_err(|error| invalid(&error))?;
+        toml::from_str(&content).map_err(|error| invalid(&error))
+    }
+
+    /// Overlay the `[security]` table of the journal directory's own `.jou.toml`, so each directory
+    /// can pin its own settings. A directory can be synced or shared, so it can't loosen the user's
+    /// limits: `max_work_factor` is refused, and `shred` can only be turned on.
+    pub fn load_vault(&mut self) -> io::Result<()> {
+        let path = self.path.join(".jou.toml");
+        let invalid = |error: &dyn fmt::Display| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), error));
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        let mut vault: toml::Table = toml::from_str(&content).map_err(|error| invalid(&error))?;
+        if let Some(toml::Value::Table(overrides)) = vault.remove("security") {
+            if overrides.contains_key("max_work_factor") {
+                return Err(invalid(&Locale::detect(self.locale).text(Message::VaultMaxWorkFactor)))
+            }
+            let shred = self.security.shred;
+            let mut security = toml::Table::try_from(&self.security).map_err(|error| invalid(&error))?;
+            security.extend(overrides);
+            self.security = security.try_into().map_err(|error| invalid(&error))?;
+            self.security.shred |= shred;
+        }
+        if let Some(key) = vault.keys().next() {
+            return Err(invalid(&Locale::detect(self.locale).format(Message::UnknownVaultTable, &[key])))
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn date_style(&self) -> DateStyle {
+        DateStyle {
+            calendar: self.calendar,
+            persian_digits: self.persian_digits,
+        }
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap()
+    }
+}
+
+/// Order of the journal list, newest first.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    /// By when entries were written
+    Created,
+    /// By when entries were last edited
+    Modified,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub border: Color,
+    pub text: Color,
+    pub error: Color,
+    /// Background of tag chips in the list
+    pub tag: Color,
+    /// Background of the selected row. The row is reversed when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected: Option<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            border: Color::Reset,
+            text: Color::Reset,
+            error: Color::Red,
+            tag: Color::Cyan,
+            selected: None,
+        }
+    }
+}
+
+/// A numeric front-matter field, like `mood` from 1 to 10.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+impl Field {
+    /// Whether `value` is within the field's range.
+    #[inline]
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+
+    /// `input` as a value of the field, or why it isn't one. A `mood` is also a whole number in
+    /// `MOOD`, like the mood of an entry.
+    pub fn parse(&self, input: &str, locale: Locale) -> Result<f64, String> {
+        match input.parse::<f64>() {
+            Ok(value) if value.is_finite() && self.name == "mood" && parse_mood(input).is_none() => {
+                Err(locale.format(Message::InvalidMood, &[&input, MOOD.start(), MOOD.end()]))
+            }
+            Ok(value) if value.is_finite() && self.contains(value) => Ok(value),
+            Ok(_) => Err(locale.format(Message::OutOfRange, &[&input, &self.range().unwrap_or_default()])),
+            Err(_) => Err(locale.format(Message::NotANumber, &[&input])),
+        }
+    }
+
+    /// The range of the field, like `1–10`, or `None` when it takes any number.
+    pub fn range(&self) -> Option<String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => Some(format!("{}–{}", min, max)),
+            (Some(min), None) => Some(format!("≥ {}", min)),
+            (None, Some(max)) => Some(format!("≤ {}", max)),
+            (None, None) => None,
+        }
+    }
+
+    /// The name with the range, like `mood 1–10`.
+    pub fn label(&self) -> String {
+        match self.range() {
+            Some(range) => format!("{} {}", self.name, range),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Layout of the journal list, and the preview of the selected entry beside it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Panes {
+    pub preview: bool,
+    /// Percentage of the width taken by the list when the preview is shown
+    pub list_width: u16,
+}
+
+impl Default for Panes {
+    fn default() -> Self {
+        Panes {
+            preview: true,
+            list_width: 35,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Security {
+    /// Character shown in place of each passphrase character
+    pub mask_char: char,
+    /// scrypt work factor (log2 of N) new entries are encrypted with. Picked to take about a
+    /// second on this machine when unset. `jou bench-kdf` helps choosing one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_factor: Option<u8>,
+    /// Highest scrypt work factor accepted when decrypting. Four more than what takes a second on
+    /// this machine when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_work_factor: Option<u8>,
+    /// Always shred deleted entries instead of moving them to the trash
+    pub shred: bool,
+}
+
+impl Default for Security {
+    fn default() -> Self {
+        Security {
+            mask_char: '\u{2022}',
+            work_factor: None,
+            max_work_factor: None,
+            shred: false,
+        }
+    }
+}
+
+/// A key with its modifiers, written like `q`, `G`, `enter`, `ctrl-d` or `alt-pagedown`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyBinding {
+    key: Key,
+    ctrl: bool,
+    alt: bool,
+}
+
+impl KeyBinding {
+    pub const fn new(key: Key) -> Self {
+        KeyBinding { key, ctrl: false, alt: false }
+    }
+
+    pub const fn ctrl(key: Key) -> Self {
+        KeyBinding { key, ctrl: true, alt: false }
+    }
+
+    pub fn matches(&self, input: &Input) -> bool {
+        self.key == input.key && self.ctrl == input.ctrl && self.alt == input.alt
+    }
+}
+
+const KEY_NAMES: [(&str, Key); 14] = [
+    ("enter", Key::Enter),
+    ("esc", Key::Esc),
+    ("tab", Key::Tab),
+    ("backspace", Key::Backspace),
+    ("delete", Key::Delete),
+    ("up", Key::Up),
+    ("down", Key::Down),
+    ("left", Key::Left),
+    ("right", Key::Right),
+    ("home", Key::Home),
+    ("end", Key::End),
+    ("pageup", Key::PageUp),
+    ("pagedown", Key::PageDown),
+    ("space", Key::Char(' ')),
+];
+
+impl KeyBinding {
+    /// Parse a key written like `ctrl-d`, with an error message in `locale` when it isn't one.
+    pub fn parse(input: &str, locale: Locale) -> Result<Self, String> {
+        let mut binding = KeyBinding::new(Key::Null);
+        let mut rest = input;
+        loop {
+            if let Some(stripped) = rest.strip_prefix("ctrl-") {
+                binding.ctrl = true;
+                rest = stripped;
+            } else if let Some(stripped) = rest.strip_prefix("alt-") {
+                binding.alt = true;
+                rest = stripped;
+            } else {
+                break;
+            }
+        }
+        let mut chars = rest.chars();
+        binding.key = match (chars.next(), chars.next()) {
+            (Some(c), None) => Key::Char(c),
+            _ => {
+                let lowercase = rest.to_lowercase();
+                if let Some((_, key)) = KEY_NAMES.iter().find(|(name, _)| *name == lowercase) {
+                    *key
+                } else if let Some(Ok(n)) = lowercase.strip_prefix('f').map(str::parse) {
+                    Key::F(n)
+                } else {
+                    return Err(locale.format(Message::InvalidKey, &[&input]))
+                }
+            }
+        };
+        Ok(binding)
+    }
+}
+
+/// Parse every key of the `[keys]` table of a config, so mistakes are reported in `locale`.
+fn check_keys(config: &toml::Table, locale: Locale) -> Result<(), String> {
+    let Some(toml::Value::Table(keys)) = config.get("keys") else {
+        return Ok(())
+    };
+    let bindings = keys.values()
+        .filter_map(toml::Value::as_array)
+        .flatten()
+        .filter_map(toml::Value::as_str);
+    for binding in bindings {
+        KeyBinding::parse(binding, locale)?;
+    }
+    Ok(())
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+    /// `Config::load` has checked the keys in the configured locale by now.
+    fn try_from(value: String) -> Result<Self, String> {
+        KeyBinding::parse(&value, Locale::default())
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "ctrl-")?;
+        }
+        if self.alt {
+            write!(f, "alt-")?;
+        }
+        match self.key {
+            Key::Char(' ') => write!(f, "space"),
+            Key::Char(c) => write!(f, "{}", c),
+            Key::F(n) => write!(f, "f{}", n),
+            key => {
+                let name = KEY_NAMES.iter().find(|(_, k)| *k == key).map_or("null", |(name, _)| name);
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
+
+impl From<KeyBinding> for String {
+    fn from(value: KeyBinding) -> Self {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Add,
+    Edit,
+    Delete,
+    Undo,
+    History,
+    Open,
+    Filter,
+    ClearFilter,
+    Search,
+    Calendar,
+    Tags,
+    Stats,
+    OnThisDay,
+    Random,
+    ToggleSort,
+    Down,
+    Up,
+    Top,
+    Bottom,
+    HalfPageDown,
+    HalfPageUp,
+    PageDown,
+    PageUp,
+    NextEntry,
+    PreviousEntry,
+    SearchBackward,
+    NextMatch,
+    PreviousMatch,
+    TogglePreview,
+    GrowList,
+    ShrinkList,
+    Restore,
+    DiscardDraft,
+    RenameTag,
+    Yes,
+    No,
+    Shred,
+    ScrollDown,
+    ScrollUp,
+    CalendarView,
+    CalendarWords,
+    PreviousDay,
+    NextDay,
+    PreviousMonth,
+    NextMonth,
+    PreviousYear,
+    NextYear,
+    Today,
+    Save,
+    SaveClose,
+    Discard,
+}
+
+/// Keybindings of the journal list, the pager and the other screens. Each action takes a list of
+/// keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keys {
+    pub quit: Vec<KeyBinding>,
+    pub add: Vec<KeyBinding>,
+    pub edit: Vec<KeyBinding>,
+    pub delete: Vec<KeyBinding>,
+    pub undo: Vec<KeyBinding>,
+    pub history: Vec<KeyBinding>,
+    pub open: Vec<KeyBinding>,
+    pub filter: Vec<KeyBinding>,
+    pub clear_filter: Vec<KeyBinding>,
+    pub search: Vec<KeyBinding>,
+    pub calendar: Vec<KeyBinding>,
+    /// Browse tags in a sidebar, filtering the list by the selected one
+    pub tags: Vec<KeyBinding>,
+    /// Charts of numeric fields like mood over time
+    pub stats: Vec<KeyBinding>,
+    /// Only list entries written on this day in earlier years, or everything again
+    pub on_this_day: Vec<KeyBinding>,
+    /// Open a random entry, for serendipity
+    pub random: Vec<KeyBinding>,
+    /// Switch the list between newest written and newest edited first
+    pub toggle_sort: Vec<KeyBinding>,
+    pub down: Vec<KeyBinding>,
+    pub up: Vec<KeyBinding>,
+    pub top: Vec<KeyBinding>,
+    pub bottom: Vec<KeyBinding>,
+    pub half_page_down: Vec<KeyBinding>,
+    pub half_page_up: Vec<KeyBinding>,
+    pub page_down: Vec<KeyBinding>,
+    pub page_up: Vec<KeyBinding>,
+    /// Open the next entry without leaving the pager
+    pub next_entry: Vec<KeyBinding>,
+    pub previous_entry: Vec<KeyBinding>,
+    /// Search the pager upwards. `search` searches it downwards
+    pub search_backward: Vec<KeyBinding>,
+    /// Next pager match, in the direction of the search
+    pub next_match: Vec<KeyBinding>,
+    pub previous_match: Vec<KeyBinding>,
+    pub toggle_preview: Vec<KeyBinding>,
+    /// Widen the list, narrowing the preview
+    pub grow_list: Vec<KeyBinding>,
+    pub shrink_list: Vec<KeyBinding>,
+    /// Restore the selected revision, or recover the selected draft
+    pub restore: Vec<KeyBinding>,
+    pub discard_draft: Vec<KeyBinding>,
+    /// Rename the tag selected in the tag sidebar
+    pub rename_tag: Vec<KeyBinding>,
+    /// Answer a confirmation
+    pub yes: Vec<KeyBinding>,
+    pub no: Vec<KeyBinding>,
+    /// Shred instead of moving to the trash, when confirming a delete
+    pub shred: Vec<KeyBinding>,
+    /// Scroll the changes of the revision or draft, while `down` and `up` pick another one
+    pub scroll_down: Vec<KeyBinding>,
+    pub scroll_up: Vec<KeyBinding>,
+    /// Switch the calendar between a month and the whole year
+    pub calendar_view: Vec<KeyBinding>,
+    /// Switch the calendar between counting entries and words
+    pub calendar_words: Vec<KeyBinding>,
+    /// Move the calendar by a day, while `down` and `up` move it by a week. The calendar's keys
+    /// take precedence over `open`, which opens the selected day
+    pub previous_day: Vec<KeyBinding>,
+    pub next_day: Vec<KeyBinding>,
+    pub previous_month: Vec<KeyBinding>,
+    pub next_month: Vec<KeyBinding>,
+    pub previous_year: Vec<KeyBinding>,
+    pub next_year: Vec<KeyBinding>,
+    /// Move the calendar back to today
+    pub today: Vec<KeyBinding>,
+    /// Save the entry and keep editing, in the builtin editor
+    pub save: Vec<KeyBinding>,
+    pub save_close: Vec<KeyBinding>,
+    /// Close the builtin editor without saving
+    pub discard: Vec<KeyBinding>,
+}
+
+const fn char_key(c: char) -> KeyBinding {
+    KeyBinding::new(Key::Char(c))
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Keys {
+            quit: vec![char_key('q')],
+            add: vec![char_key('a')],
+            edit: vec![char_key('e')],
+            delete: vec![char_key('D')],
+            undo: vec![char_key('u')],
+            history: vec![char_key('H')],
+            open: vec![char_key('l'), KeyBinding::new(Key::Enter)],
+            filter: vec![char_key('f')],
+            clear_filter: vec![char_key('F')],
+            search: vec![char_key('/')],
+            calendar: vec![char_key('c')],
+            tags: vec![char_key('t')],
+            stats: vec![char_key('s')],
+            on_this_day: vec![char_key('y')],
+            random: vec![char_key('r')],
+            toggle_sort: vec![char_key('o')],
+            down: vec![char_key('j'), KeyBinding::new(Key::Down)],
+            up: vec![char_key('k'), KeyBinding::new(Key::Up)],
+            top: vec![char_key('g'), KeyBinding::new(Key::Home)],
+            bottom: vec![char_key('G'), KeyBinding::new(Key::End)],
+            half_page_down: vec![KeyBinding::ctrl(Key::Char('d'))],
+            half_page_up: vec![KeyBinding::ctrl(Key::Char('u'))],
+            page_down: vec![KeyBinding::new(Key::PageDown), char_key(' ')],
+            page_up: vec![KeyBinding::new(Key::PageUp)],
+            next_entry: vec![char_key(']')],
+            previous_entry: vec![char_key('[')],
+            search_backward: vec![char_key('?')],
+            next_match: vec![char_key('n')],
+            previous_match: vec![char_key('N')],
+            toggle_preview: vec![char_key('p')],
+            grow_list: vec![char_key('>')],
+            shrink_list: vec![char_key('<')],
+            restore: vec![char_key('r')],
+            discard_draft: vec![char_key('d')],
+            rename_tag: vec![char_key('r')],
+            yes: vec![char_key('y'), char_key('Y'), KeyBinding::new(Key::Enter)],
+            no: vec![char_key('n'), char_key('N'), KeyBinding::new(Key::Esc), char_key('q')],
+            shred: vec![char_key('s')],
+            scroll_down: vec![char_key('J')],
+            scroll_up: vec![char_key('K')],
+            calendar_view: vec![char_key('v')],
+            calendar_words: vec![char_key('w')],
+            previous_day: vec![char_key('h'), KeyBinding::new(Key::Left)],
+            next_day: vec![char_key('l'), KeyBinding::new(Key::Right)],
+            previous_month: vec![char_key('['), KeyBinding::new(Key::PageUp)],
+            next_month: vec![char_key(']'), KeyBinding::new(Key::PageDown)],
+            previous_year: vec![char_key('{')],
+            next_year: vec![char_key('}')],
+            today: vec![char_key('t')],
+            save: vec![KeyBinding::ctrl(Key::Char('s'))],
+            save_close: vec![KeyBinding::new(Key::Esc)],
+            discard: vec![KeyBinding::ctrl(Key::Char('q'))],
+        }
+    }
+}
+
+impl Keys {
+    /// The keys bound to `action`.
+    pub fn bindings(&self, action: Action) -> &[KeyBinding] {
+        match action {
+            Action::Quit => &self.quit,
+            Action::Add => &self.add,
+            Action::Edit => &self.edit,
+            Action::Delete => &self.delete,
+            Action::Undo => &self.undo,
+            Action::History => &self.history,
+            Action::Open => &self.open,
+            Action::Filter => &self.filter,
+            Action::ClearFilter => &self.clear_filter,
+            Action::Search => &self.search,
+            Action::Calendar => &self.calendar,
+            Action::Tags => &self.tags,
+            Action::Stats => &self.stats,
+            Action::OnThisDay => &self.on_this_day,
+            Action::Random => &self.random,
+            Action::ToggleSort => &self.toggle_sort,
+            Action::Down => &self.down,
+            Action::Up => &self.up,
+            Action::Top => &self.top,
+            Action::Bottom => &self.bottom,
+            Action::HalfPageDown => &self.half_page_down,
+            Action::HalfPageUp => &self.half_page_up,
+            Action::PageDown => &self.page_down,
+            Action::PageUp => &self.page_up,
+            Action::NextEntry => &self.next_entry,
+            Action::PreviousEntry => &self.previous_entry,
+            Action::SearchBackward => &self.search_backward,
+            Action::NextMatch => &self.next_match,
+            Action::PreviousMatch => &self.previous_match,
+            Action::TogglePreview => &self.toggle_preview,
+            Action::GrowList => &self.grow_list,
+            Action::ShrinkList => &self.shrink_list,
+            Action::Restore => &self.restore,
+            Action::DiscardDraft => &self.discard_draft,
+            Action::RenameTag => &self.rename_tag,
+            Action::Yes => &self.yes,
+            Action::No => &self.no,
+            Action::Shred => &self.shred,
+            Action::ScrollDown => &self.scroll_down,
+            Action::ScrollUp => &self.scroll_up,
+            Action::CalendarView => &self.calendar_view,
+            Action::CalendarWords => &self.calendar_words,
+            Action::PreviousDay => &self.previous_day,
+            Action::NextDay => &self.next_day,
+            Action::PreviousMonth => &self.previous_month,
+            Action::NextMonth => &self.next_month,
+            Action::PreviousYear => &self.previous_year,
+            Action::NextYear => &self.next_year,
+            Action::Today => &self.today,
+            Action::Save => &self.save,
+            Action::SaveClose => &self.save_close,
+            Action::Discard => &self.discard,
+        }
+    }
+
+    /// Whether `input` is bound to `action`. Actions of a single screen, like restoring a revision,
+    /// are checked this way since their keys can be bound elsewhere too.
+    #[inline]
+    pub fn matches(&self, action: Action, input: &Input) -> bool {
+        self.bindings(action).iter().any(|binding| binding.matches(input))
+    }
+
+    /// The first key bound to `action`, for hints like `r to restore`.
+    pub fn hint(&self, action: Action) -> String {
+        self.bindings(action).first().map_or_else(String::new, KeyBinding::to_string)
+    }
+
+    /// The first key bound to `action` but none of `preceding`, for hints on screens where
+    /// `preceding` take precedence, like the calendar's `next_day` over `open`.
+    pub fn hint_besides(&self, action: Action, preceding: &[Action]) -> String {
+        self.bindings(action).iter()
+            .find(|binding| !preceding.iter().any(|other| self.bindings(*other).contains(binding)))
+            .map_or_else(String::new, KeyBinding::to_string)
+    }
+
+    /// The builtin editor's action bound to `input`, if any. Other keys are typed into the entry.
+    pub fn editor_action(&self, input: &Input) -> Option<Action> {
+        [Action::Save, Action::SaveClose, Action::Discard].into_iter()
+            .find(|action| self.matches(*action, input))
+    }
+
+    /// The action bound to `input` in the list and the pager, if any.
+    pub fn action(&self, input: &Input) -> Option<Action> {
+        let actions = [
+            Action::Quit, Action::Add, Action::Edit, Action::Delete, Action::Undo, Action::History,
+            Action::Open, Action::Filter, Action::ClearFilter, Action::Search, Action::Calendar,
+            Action::Tags, Action::Stats, Action::OnThisDay, Action::Random, Action::ToggleSort,
+            Action::Down, Action::Up, Action::Top, Action::Bottom, Action::HalfPageDown,
+            Action::HalfPageUp, Action::PageDown, Action::PageUp, Action::NextEntry,
+            Action::PreviousEntry, Action::SearchBackward, Action::NextMatch, Action::PreviousMatch,
+            Action::TogglePreview, Action::GrowList, Action::ShrinkList,
+        ];
+        actions.into_iter().find(|action| self.matches(*action, input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, min: Option<f64>, max: Option<f64>) -> Field {
+        Field { name: name.to_string(), min, max }
+    }
+
+    #[test]
+    fn field_values() {
+        let mood = field("mood", Some(1.0), Some(10.0));
+        assert_eq!(mood.parse("7", Locale::En), Ok(7.0));
+        assert_eq!(mood.parse("1", Locale::En), Ok(1.0));
+        assert_eq!(mood.parse("10", Locale::En), Ok(10.0));
+        assert_eq!(mood.parse("7.5", Locale::En), Err("7.5 isn't a whole number from 1 to 10".to_string()));
+        assert_eq!(mood.parse("7.0", Locale::En), Err("7.0 isn't a whole number from 1 to 10".to_string()));
+        assert_eq!(field("mood", Some(0.0), Some(20.0)).parse("15", Locale::En), Err("15 isn't a whole number from 1 to 10".to_string()));
+        assert_eq!(mood.parse("11", Locale::En), Err("11 isn't a whole number from 1 to 10".to_string()));
+        assert_eq!(mood.parse("0", Locale::En), Err("0 isn't a whole number from 1 to 10".to_string()));
+        let sleep = field("sleep", Some(0.0), Some(24.0));
+        assert_eq!(sleep.parse("7.5", Locale::En), Ok(7.5));
+        assert_eq!(sleep.parse("25", Locale::En), Err("25 is outside 0–24".to_string()));
+        assert_eq!(field("mood", Some(1.0), Some(5.0)).parse("7", Locale::En), Err("7 is outside 1–5".to_string()));
+        assert_eq!(mood.parse("good", Locale::En), Err("good isn't a number".to_string()));
+        assert!(mood.parse("NaN", Locale::En).is_err());
+        assert!(mood.parse("inf", Locale::En).is_err());
+    }
+
+    #[test]
+    fn open_ranges() {
+        assert_eq!(field("sleep", None, None).parse("-3", Locale::En), Ok(-3.0));
+        assert!(field("sleep", None, None).parse("inf", Locale::En).is_err());
+        assert!(field("sleep", Some(0.0), None).parse("-1", Locale::En).is_err());
+        assert_eq!(field("sleep", None, Some(24.0)).parse("8", Locale::En), Ok(8.0));
+        assert_eq!(field("sleep", None, Some(24.0)).label(), "sleep ≤ 24");
+        assert_eq!(field("sleep", None, None).label(), "sleep");
+    }
+
+    #[test]
+    fn vaults_only_tighten_security() {
+        let dir = std::env::temp_dir().join(format!("jou-vault-{:016x}", rand::random::<u64>()));
+        fs::create_dir_all(&dir).unwrap();
+        let load = |vault: &str, shred: bool| {
+            fs::write(dir.join(".jou.toml"), vault).unwrap();
+            let mut config = Config { path: dir.clone(), ..Config::default() };
+            config.security.max_work_factor = Some(20);
+            config.security.shred = shred;
+            config.load_vault().map(|()| config.security)
+        };
+        let security = load("[security]\nwork_factor = 14\nshred = true\n", false).unwrap();
+        assert_eq!((security.work_factor, security.max_work_factor, security.shred), (Some(14), Some(20), true));
+        assert!(load("[security]\nshred = false\n", true).unwrap().shred);
+        assert!(load("[security]\nmax_work_factor = 40\n", false).is_err());
+        assert!(load("[theme]\n", false).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
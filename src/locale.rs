@@ -0,0 +1,210 @@
+use std::{env, fmt};
+use serde::{Deserialize, Serialize};
+
+mod en;
+mod fa;
+
+/// Language of the interface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Fa,
+}
+
+/// Every piece of text the interface shows. `{}` in a message is replaced by its arguments, in
+/// order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    // Passphrase
+    Passphrase,
+    InitializePassphrase,
+    WrongPassphrase,
+    WorkFactorAboveMaximum,
+    EnterPassphrase,
+    // Journal list and preview
+    Journals,
+    JournalsByLastEdit,
+    Preview,
+    Decrypting,
+    Word,
+    Words,
+    FilterByDate,
+    Search,
+    Tags,
+    RenameTag,
+    OnThisDay,
+    YearAgo,
+    YearsAgo,
+    NothingOnThisDay,
+    // Editor
+    FieldPrompt,
+    NotANumber,
+    OutOfRange,
+    InvalidMood,
+    UnknownField,
+    NewJournal,
+    EditJournal,
+    Modified,
+    // Pager
+    NoMatches,
+    Match,
+    Matches,
+    // Edits
+    Edited,
+    EditedTimes,
+    WithinAMinute,
+    MinuteLater,
+    MinutesLater,
+    HourLater,
+    HoursLater,
+    DayLater,
+    DaysLater,
+    MonthLater,
+    MonthsLater,
+    YearLater,
+    YearsLater,
+    // History and drafts
+    Revisions,
+    NoRevisions,
+    ChangesSinceRevision,
+    UnsavedDrafts,
+    ChangesInDraft,
+    NewDraft,
+    EditDraft,
+    // Confirmations
+    Confirm,
+    ConfirmDelete,
+    ConfirmDiscard,
+    ConfirmDeleteEmptied,
+    ConfirmDiscardDraft,
+    // Statistics
+    Stats,
+    StatsHint,
+    NoFields,
+    Writing,
+    TotalEntries,
+    TotalWords,
+    AverageLength,
+    LongestStreak,
+    CurrentStreak,
+    ByWeekday,
+    ByHour,
+    ByMonth,
+    // Calendar
+    CalendarByEntries,
+    CalendarByWords,
+    CalendarHint,
+    CalendarMoveHint,
+    DayNoEntries,
+    DayOneEntry,
+    DayEntries,
+    // Gregorian calendar
+    January,
+    February,
+    March,
+    April,
+    May,
+    June,
+    July,
+    August,
+    September,
+    October,
+    November,
+    December,
+    MondayInitial,
+    TuesdayInitial,
+    WednesdayInitial,
+    ThursdayInitial,
+    FridayInitial,
+    SaturdayInitial,
+    SundayInitial,
+    // Command line
+    Error,
+    IncorrectPassphrase,
+    ExcessiveWork,
+    NoEntry,
+    TrashEntry,
+    RemovedEntries,
+    IndexedEntries,
+    MigratedEntries,
+    RenamedTag,
+    InvalidTag,
+    FieldSummary,
+    KdfTime,
+    KdfRecommended,
+    KdfTooSlow,
+    // Errors
+    EntryExists,
+    NewerEntryVersion,
+    InvalidDate,
+    InvalidKey,
+    UnknownVaultTable,
+    VaultMaxWorkFactor,
+    EmptyEditor,
+    InvalidEditor,
+}
+
+const MONTHS: [Message; 12] = [
+    Message::January, Message::February, Message::March, Message::April, Message::May,
+    Message::June, Message::July, Message::August, Message::September, Message::October,
+    Message::November, Message::December,
+];
+
+const WEEKDAY_INITIALS: [Message; 7] = [
+    Message::MondayInitial, Message::TuesdayInitial, Message::WednesdayInitial,
+    Message::ThursdayInitial, Message::FridayInitial, Message::SaturdayInitial,
+    Message::SundayInitial,
+];
+
+impl Locale {
+    /// `configured`, or else the language of `LC_ALL`, `LC_MESSAGES` or `LANG`. English when
+    /// there's no translation for it.
+    pub fn detect(configured: Option<Locale>) -> Self {
+        if let Some(locale) = configured {
+            return locale
+        }
+        let language = ["LC_ALL", "LC_MESSAGES", "LANG"].into_iter()
+            .filter_map(|variable| env::var(variable).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        match language.split(['_', '.', '@']).next() {
+            Some("fa") => Locale::Fa,
+            _ => Locale::En,
+        }
+    }
+
+    #[inline]
+    pub fn text(self, message: Message) -> &'static str {
+        match self {
+            Locale::En => en::text(message),
+            Locale::Fa => fa::text(message),
+        }
+    }
+
+    /// The name of the Gregorian `month`, from 1 for January.
+    #[inline]
+    pub fn month(self, month: u32) -> &'static str {
+        MONTHS.get(month.wrapping_sub(1) as usize).map_or("", |month| self.text(*month))
+    }
+
+    /// Initials of the weekdays, from Monday.
+    pub fn weekday_initials(self) -> [&'static str; 7] {
+        WEEKDAY_INITIALS.map(|day| self.text(day))
+    }
+
+    /// `message` with each `{}` replaced by the next of `args`.
+    pub fn format(self, message: Message, args: &[&dyn fmt::Display]) -> String {
+        let mut args = args.iter();
+        let mut pieces = self.text(message).split("{}");
+        let mut result = pieces.next().unwrap_or_default().to_string();
+        for piece in pieces {
+            if let Some(arg) = args.next() {
+                result.push_str(&arg.to_string());
+            }
+            result.push_str(piece);
+        }
+        result
+    }
+}
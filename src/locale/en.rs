@@ -0,0 +1,121 @@
+use super::Message;
+
+pub fn text(message: Message) -> &'static str {
+    match message {
+        Message::Passphrase => "Passphrase",
+        Message::InitializePassphrase => "Initialize directory passphrase",
+        Message::WrongPassphrase => "Wrong passphrase",
+        Message::WorkFactorAboveMaximum => "Work factor {} is above the maximum",
+        Message::EnterPassphrase => "Enter your password",
+        Message::Journals => "Journals",
+        Message::JournalsByLastEdit => "Journals by last edit",
+        Message::Preview => "Preview",
+        Message::Decrypting => "Decrypting...",
+        Message::Word => "{} word",
+        Message::Words => "{} words",
+        Message::FilterByDate => "Filter by date (since..until)",
+        Message::Search => "Search",
+        Message::Tags => "Tags",
+        Message::RenameTag => "Rename #{} to",
+        Message::OnThisDay => "On this day, {}",
+        Message::YearAgo => "{}, 1 year ago",
+        Message::YearsAgo => "{}, {} years ago",
+        Message::NothingOnThisDay => "Nothing was written on this day in earlier years",
+        Message::NewJournal => "Write your new journal",
+        Message::EditJournal => "Edit your journal",
+        Message::FieldPrompt => "{} (enter to skip)",
+        Message::NotANumber => "{} isn't a number",
+        Message::OutOfRange => "{} is outside {}",
+        Message::InvalidMood => "{} isn't a whole number from {} to {}",
+        Message::UnknownField => "No field {}: it isn't configured and no entry has it",
+        Message::Modified => "{} [modified]",
+        Message::NoMatches => "no matches",
+        Message::Match => "match {}/{}",
+        Message::Matches => "{} matches",
+        Message::Edited => "edited {}",
+        Message::EditedTimes => "edited {} ({} edits)",
+        Message::WithinAMinute => "within a minute",
+        Message::MinuteLater => "1 minute later",
+        Message::MinutesLater => "{} minutes later",
+        Message::HourLater => "1 hour later",
+        Message::HoursLater => "{} hours later",
+        Message::DayLater => "1 day later",
+        Message::DaysLater => "{} days later",
+        Message::MonthLater => "1 month later",
+        Message::MonthsLater => "{} months later",
+        Message::YearLater => "1 year later",
+        Message::YearsLater => "{} years later",
+        Message::Revisions => "Revisions",
+        Message::NoRevisions => "No revisions",
+        Message::ChangesSinceRevision => "Changes since revision ({}/{} to scroll, {} to restore)",
+        Message::UnsavedDrafts => "Unsaved drafts",
+        Message::ChangesInDraft => "Changes in draft ({}/{} to scroll, {} to recover, {} to discard, esc for later)",
+        Message::NewDraft => "New {}",
+        Message::EditDraft => "Edit {}",
+        Message::Confirm => "Confirm",
+        Message::ConfirmDelete => "Delete {}? ({}/{}, {} to shred)",
+        Message::ConfirmDiscard => "Discard unsaved changes? ({}/{})",
+        Message::ConfirmDeleteEmptied => "The entry is empty. Delete it? ({} to delete, {} to discard the changes)",
+        Message::ConfirmDiscardDraft => "Discard this draft? ({}/{})",
+        Message::Stats => "Statistics",
+        Message::StatsHint => "{}/{} field",
+        Message::NoFields => "No entries with numeric fields like `mood: 7` yet",
+        Message::Writing => "Writing",
+        Message::TotalEntries => "Entries: {}",
+        Message::TotalWords => "Words: {}",
+        Message::AverageLength => "Average length: {} words",
+        Message::LongestStreak => "Longest streak: {} days",
+        Message::CurrentStreak => "Current streak: {} days",
+        Message::ByWeekday => "By weekday",
+        Message::ByHour => "By hour",
+        Message::ByMonth => "By month",
+        Message::CalendarByEntries => "Calendar by entries",
+        Message::CalendarByWords => "Calendar by words",
+        Message::CalendarHint => "{} year/month, {} words/entries, {} to open",
+        Message::CalendarMoveHint => "{}/{} day, {}/{} week, {}/{} month, {}/{} year, {} today",
+        Message::DayNoEntries => "{}: no entries",
+        Message::DayOneEntry => "{}: 1 entry, {} words",
+        Message::DayEntries => "{}: {} entries, {} words",
+        Message::January => "January",
+        Message::February => "February",
+        Message::March => "March",
+        Message::April => "April",
+        Message::May => "May",
+        Message::June => "June",
+        Message::July => "July",
+        Message::August => "August",
+        Message::September => "September",
+        Message::October => "October",
+        Message::November => "November",
+        Message::December => "December",
+        Message::MondayInitial => "Mo",
+        Message::TuesdayInitial => "Tu",
+        Message::WednesdayInitial => "We",
+        Message::ThursdayInitial => "Th",
+        Message::FridayInitial => "Fr",
+        Message::SaturdayInitial => "Sa",
+        Message::SundayInitial => "Su",
+        Message::Error => "Error: {}",
+        Message::IncorrectPassphrase => "Error: Incorrect password",
+        Message::ExcessiveWork => "Error: Entries need a work factor of {}, more than security.max_work_factor allows",
+        Message::NoEntry => "Error: no entry {}",
+        Message::TrashEntry => "{}\tdeleted {}, removed in {} days",
+        Message::RemovedEntries => "Removed {} entries",
+        Message::IndexedEntries => "Indexed {} entries",
+        Message::MigratedEntries => "Migrated {} entries",
+        Message::RenamedTag => "Renamed #{} to #{} in {} entries",
+        Message::InvalidTag => "Tags are letters, digits, - and _ only, not \"{}\"",
+        Message::FieldSummary => "{}: {} entries, min {}, mean {}, max {}, last {}",
+        Message::KdfTime => "work_factor = {}: {}",
+        Message::KdfRecommended => "Recommended for unlocking within {}:",
+        Message::KdfTooSlow => "Even the lowest work factor takes longer than {}",
+        Message::EntryExists => "{} already exists",
+        Message::NewerEntryVersion => "{} was written by a newer version of jou (entry version {})",
+        Message::InvalidDate => "invalid date: {}",
+        Message::InvalidKey => "invalid key: {}",
+        Message::UnknownVaultTable => "unknown table `{}`, only `security` can be set per directory",
+        Message::VaultMaxWorkFactor => "`security.max_work_factor` can't be set per directory, only in the user config",
+        Message::EmptyEditor => "empty editor command",
+        Message::InvalidEditor => "can't parse editor command {}: {}",
+    }
+}
@@ -0,0 +1,121 @@
+use super::Message;
+
+pub fn text(message: Message) -> &'static str {
+    match message {
+        Message::Passphrase => "گذرواژه",
+        Message::InitializePassphrase => "تعیین گذرواژه‌ی پوشه",
+        Message::WrongPassphrase => "گذرواژه نادرست است",
+        Message::WorkFactorAboveMaximum => "ضریب کار {} از بیشینه بالاتر است",
+        Message::EnterPassphrase => "گذرواژه را وارد کنید",
+        Message::Journals => "یادداشت‌ها",
+        Message::JournalsByLastEdit => "یادداشت‌ها به ترتیب آخرین ویرایش",
+        Message::Preview => "پیش‌نمایش",
+        Message::Decrypting => "در حال رمزگشایی...",
+        Message::Word => "{} واژه",
+        Message::Words => "{} واژه",
+        Message::FilterByDate => "پالایش بر پایه‌ی تاریخ (از..تا)",
+        Message::Search => "جستجو",
+        Message::Tags => "برچسب‌ها",
+        Message::RenameTag => "تغییر نام #{} به",
+        Message::OnThisDay => "در چنین روزی، {}",
+        Message::YearAgo => "{}، یک سال پیش",
+        Message::YearsAgo => "{}، {} سال پیش",
+        Message::NothingOnThisDay => "در سال‌های پیش در چنین روزی چیزی نوشته نشده",
+        Message::NewJournal => "یادداشت تازه‌تان را بنویسید",
+        Message::EditJournal => "ویرایش یادداشت",
+        Message::FieldPrompt => "{} (enter برای رد شدن)",
+        Message::NotANumber => "{} عدد نیست",
+        Message::OutOfRange => "{} بیرون از {} است",
+        Message::InvalidMood => "{} عدد صحیحی از {} تا {} نیست",
+        Message::UnknownField => "فیلد {} وجود ندارد: نه تنظیم شده و نه در یادداشتی آمده",
+        Message::Modified => "{} [تغییر کرده]",
+        Message::NoMatches => "موردی پیدا نشد",
+        Message::Match => "مورد {} از {}",
+        Message::Matches => "{} مورد",
+        Message::Edited => "ویرایش‌شده {}",
+        Message::EditedTimes => "ویرایش‌شده {} ({} ویرایش)",
+        Message::WithinAMinute => "در همان دقیقه",
+        Message::MinuteLater => "یک دقیقه بعد",
+        Message::MinutesLater => "{} دقیقه بعد",
+        Message::HourLater => "یک ساعت بعد",
+        Message::HoursLater => "{} ساعت بعد",
+        Message::DayLater => "یک روز بعد",
+        Message::DaysLater => "{} روز بعد",
+        Message::MonthLater => "یک ماه بعد",
+        Message::MonthsLater => "{} ماه بعد",
+        Message::YearLater => "یک سال بعد",
+        Message::YearsLater => "{} سال بعد",
+        Message::Revisions => "نسخه‌ها",
+        Message::NoRevisions => "نسخه‌ای نیست",
+        Message::ChangesSinceRevision => "تغییرات از این نسخه ({}/{} برای پیمایش، {} برای بازگرداندن)",
+        Message::UnsavedDrafts => "پیش‌نویس‌های ذخیره‌نشده",
+        Message::ChangesInDraft => "تغییرات پیش‌نویس ({}/{} برای پیمایش، {} برای بازیابی، {} برای دور انداختن، esc برای بعد)",
+        Message::NewDraft => "تازه {}",
+        Message::EditDraft => "ویرایش {}",
+        Message::Confirm => "تأیید",
+        Message::ConfirmDelete => "{} حذف شود؟ ({}/{}، {} برای نابودی کامل)",
+        Message::ConfirmDiscard => "تغییرات ذخیره‌نشده دور انداخته شود؟ ({}/{})",
+        Message::ConfirmDeleteEmptied => "نوشته خالی است. حذف شود؟ ({} برای حذف، {} برای دور انداختن تغییرات)",
+        Message::ConfirmDiscardDraft => "این پیش‌نویس دور انداخته شود؟ ({}/{})",
+        Message::Stats => "آمار",
+        Message::StatsHint => "{}/{} فیلد",
+        Message::NoFields => "هنوز یادداشتی با فیلد عددی مانند `mood: 7` نیست",
+        Message::Writing => "نوشتن",
+        Message::TotalEntries => "یادداشت‌ها: {}",
+        Message::TotalWords => "واژه‌ها: {}",
+        Message::AverageLength => "میانگین درازا: {} واژه",
+        Message::LongestStreak => "بلندترین پیاپی: {} روز",
+        Message::CurrentStreak => "پیاپی کنونی: {} روز",
+        Message::ByWeekday => "بر پایه‌ی روز هفته",
+        Message::ByHour => "بر پایه‌ی ساعت",
+        Message::ByMonth => "بر پایه‌ی ماه",
+        Message::CalendarByEntries => "تقویم بر پایه‌ی یادداشت‌ها",
+        Message::CalendarByWords => "تقویم بر پایه‌ی واژه‌ها",
+        Message::CalendarHint => "{} سال/ماه، {} واژه/یادداشت، {} برای باز کردن",
+        Message::CalendarMoveHint => "{}/{} روز، {}/{} هفته، {}/{} ماه، {}/{} سال، {} امروز",
+        Message::DayNoEntries => "{}: بی یادداشت",
+        Message::DayOneEntry => "{}: یک یادداشت، {} واژه",
+        Message::DayEntries => "{}: {} یادداشت، {} واژه",
+        Message::January => "ژانویه",
+        Message::February => "فوریه",
+        Message::March => "مارس",
+        Message::April => "آوریل",
+        Message::May => "مه",
+        Message::June => "ژوئن",
+        Message::July => "ژوئیه",
+        Message::August => "اوت",
+        Message::September => "سپتامبر",
+        Message::October => "اکتبر",
+        Message::November => "نوامبر",
+        Message::December => "دسامبر",
+        Message::MondayInitial => "د",
+        Message::TuesdayInitial => "س",
+        Message::WednesdayInitial => "چ",
+        Message::ThursdayInitial => "پ",
+        Message::FridayInitial => "ج",
+        Message::SaturdayInitial => "ش",
+        Message::SundayInitial => "ی",
+        Message::Error => "خطا: {}",
+        Message::IncorrectPassphrase => "خطا: گذرواژه نادرست است",
+        Message::ExcessiveWork => "خطا: یادداشت‌ها ضریب کار {} می‌خواهند که بیش از security.max_work_factor است",
+        Message::NoEntry => "خطا: یادداشتی با شناسه‌ی {} نیست",
+        Message::TrashEntry => "{}\tحذف‌شده در {}، تا {} روز دیگر پاک می‌شود",
+        Message::RemovedEntries => "{} یادداشت پاک شد",
+        Message::IndexedEntries => "{} یادداشت نمایه شد",
+        Message::MigratedEntries => "{} یادداشت به‌روز شد",
+        Message::RenamedTag => "#{} به #{} تغییر کرد ({} یادداشت)",
+        Message::InvalidTag => "برچسب فقط حرف، رقم، - و _ است، نه «{}»",
+        Message::FieldSummary => "{}: {} یادداشت، کمینه {}، میانگین {}، بیشینه {}، آخرین {}",
+        Message::KdfTime => "work_factor = {}: {}",
+        Message::KdfRecommended => "پیشنهاد برای باز شدن در کمتر از {}:",
+        Message::KdfTooSlow => "حتی کمترین ضریب کار بیش از {} طول می‌کشد",
+        Message::EntryExists => "{} از پیش وجود دارد",
+        Message::NewerEntryVersion => "{} با نسخه‌ی تازه‌تری از jou نوشته شده (نسخه‌ی یادداشت {})",
+        Message::InvalidDate => "تاریخ نامعتبر: {}",
+        Message::InvalidKey => "کلید نامعتبر: {}",
+        Message::UnknownVaultTable => "جدول ناشناخته‌ی `{}`، برای هر پوشه فقط `security` را می‌توان تنظیم کرد",
+        Message::VaultMaxWorkFactor => "`security.max_work_factor` را نمی‌توان برای هر پوشه تنظیم کرد، فقط در تنظیمات کاربر",
+        Message::EmptyEditor => "فرمان ویرایشگر خالی است",
+        Message::InvalidEditor => "فرمان ویرایشگر {} قابل خواندن نیست: {}",
+    }
+}
@@ -1,17 +1,18 @@
 mod tui;
 use std::{path::PathBuf, io};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use home::home_dir;
 mod app;
 mod cli;
+mod config;
+mod locale;
 use app::App;
-
-fn print_files(path: PathBuf) {
-    println!("{}", path.as_os_str().to_str().unwrap());
-}
+use config::Config;
+use locale::{Locale, Message};
+use app::file::date::{Calendar, DateFilter, Period};
 
 pub fn append_home_dir(vec: [&str; 3]) -> PathBuf {
-    let mut path = PathBuf::from(format!("{}", home_dir().unwrap().to_str().unwrap()));
+    let mut path = PathBuf::from(home_dir().unwrap().to_str().unwrap());
     for item in vec {
         path = path.join(item);
     }
@@ -19,17 +20,30 @@ pub fn append_home_dir(vec: [&str; 3]) -> PathBuf {
     path
 }
 
+/// `$XDG_DATA_HOME/jou`, falling back to `~/.local/share/jou`. That's also used while it exists and
+/// the first doesn't, since it was the default before `XDG_DATA_HOME` was honored.
 pub fn default_directory() -> PathBuf {
-    append_home_dir([".local", "share", "jou"])
+    let legacy = append_home_dir([".local", "share", "jou"]);
+    match std::env::var_os("XDG_DATA_HOME") {
+        Some(data_home) if !data_home.is_empty() => {
+            let path = PathBuf::from(data_home).join("jou");
+            if !path.exists() && legacy.exists() { legacy } else { path }
+        }
+        _ => legacy,
+    }
 }
 
 /// A good journal application a day, make therapy go away
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
-    /// Path to journal directory. Unique for each password
-    #[arg(short, long,default_value=default_directory().into_os_string())]
-    path: PathBuf,
+    /// Path to journal directory. Unique for each password [default: path from the config]
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+
+    /// Path to the config file
+    #[arg(short, long, default_value=config::default_path().into_os_string())]
+    config: PathBuf,
 
     /// Pass the passphrase non-interactively
     #[arg(short='P', long)]
@@ -42,20 +56,195 @@ pub struct Args {
     /// List in non-interactive mode
     #[arg(short='l', long)]
     list: bool,
+
+    /// Only entries written since DATE (e.g. 2024-03-04, 2024-03, yesterday, week, 3d, 2w, or Jalali like 1403/01/15)
+    #[arg(short='s', long, value_name="DATE", conflicts_with="on")]
+    since: Option<String>,
+
+    /// Only entries written until DATE, inclusive
+    #[arg(short='u', long, value_name="DATE", conflicts_with="on")]
+    until: Option<String>,
+
+    /// Only entries written on DATE (a day, a month, a year, ...)
+    #[arg(short='o', long, value_name="DATE")]
+    on: Option<String>,
+
+    /// Only entries tagged TAG, in their front-matter or as #TAG
+    #[arg(short='t', long, value_name="TAG")]
+    tag: Option<String>,
+
+    /// Order entries by when they were written or last edited [default: sort from the config]
+    #[arg(long, value_enum)]
+    sort: Option<config::Sort>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Search the content of journal entries
+    Search {
+        /// Text to search for
+        pattern: String,
+
+        /// Treat the pattern as a regular expression
+        #[arg(short='r', long, conflicts_with="fuzzy")]
+        regex: bool,
+
+        /// Match case insensitively
+        #[arg(short='i', long)]
+        ignore_case: bool,
+
+        /// Only match whole words
+        #[arg(short='w', long, conflicts_with="fuzzy")]
+        word: bool,
+
+        /// Match lines containing the pattern's characters in order
+        #[arg(short='f', long)]
+        fuzzy: bool,
+
+        /// Lines of context to print around each match
+        #[arg(short='C', long, default_value_t=0)]
+        context: usize,
+    },
+    /// Rebuild the encrypted search index
+    Reindex,
+    /// Rewrite plain text entries from older versions in the current entry format, keeping the
+    /// originals in their history
+    Migrate,
+    /// Print the effective configuration
+    Config,
+    /// Delete entries, moving them to the trash
+    Delete {
+        /// Entry IDs, as printed by `jou search`
+        #[arg(required=true)]
+        ids: Vec<String>,
+
+        /// Overwrite the entries on disk instead of moving them to the trash
+        #[arg(short='s', long)]
+        shred: bool,
+    },
+    /// List, show, compare and restore earlier revisions of an entry
+    History {
+        /// Entry ID, as printed by `jou search`
+        id: String,
+
+        /// Print the content of REVISION
+        #[arg(short='s', long, value_name="REVISION", group="revision")]
+        show: Option<String>,
+
+        /// Print the changes from REVISION to the current version
+        #[arg(short='d', long, value_name="REVISION", group="revision")]
+        diff: Option<String>,
+
+        /// Make REVISION the current version
+        #[arg(short='r', long, value_name="REVISION", group="revision")]
+        restore: Option<String>,
+    },
+    /// List the tags of the entries with how many entries have each, or rename them
+    Tags {
+        #[command(subcommand)]
+        command: Option<TagsCommand>,
+    },
+    /// Show how much and when entries were written, and summarize numeric fields like mood
+    Stats {
+        /// Only summarize this field. Can be passed more than once [default: every field]
+        #[arg(short='f', long="field", value_name="FIELD")]
+        fields: Vec<String>,
+
+        /// Print the values of each entry as CSV
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Print the entries written on this day in earlier years, grouped by year
+    OnThisDay,
+    /// Manage deleted entries
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommand,
+    },
+    /// Measure how long unlocking takes for each scrypt work factor, and recommend one
+    BenchKdf {
+        /// Longest acceptable time to decrypt a single entry, in milliseconds
+        #[arg(short='t', long, default_value_t=1000)]
+        target_ms: u64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagsCommand {
+    /// Rename a tag in every entry that has it, merging it into NEW when entries already use NEW
+    Rename {
+        old: String,
+        new: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrashCommand {
+    /// List deleted entries
+    List,
+    /// Move deleted entries back into the journal
+    Restore {
+        /// Entry IDs, as printed by `jou trash list`
+        #[arg(required=true)]
+        ids: Vec<String>,
+    },
+    /// Shred every deleted entry
+    Empty,
 }
 
 impl Args {
     pub fn is_cli(&self) -> bool {
-        self.list || !self.add.is_empty()
+        self.list || !self.add.is_empty() || self.command.is_some()
+    }
+
+    /// The filter of `--since`, `--until` and `--on`, with `week`, `month` and `year` in
+    /// `calendar`. Errors are in `locale`.
+    pub fn filter(&self, calendar: Calendar, locale: Locale) -> Result<DateFilter, String> {
+        let period = |input: &Option<String>| input.as_deref()
+            .map(|input| Period::parse_input(input, calendar, locale))
+            .transpose();
+        Ok(DateFilter::new(period(&self.since)?, period(&self.until)?, period(&self.on)?))
     }
 }
 
 fn main() -> io::Result<()>{
-    let args = Args::parse();
+    let mut args = Args::parse();
+    let path = args.path.take();
+    let config = Config::load(&args.config).and_then(|mut config| {
+        if let Some(path) = path {
+            config.path = path;
+        }
+        config.load_vault()?;
+        Ok(config)
+    });
+    let config = match config {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", Locale::detect(None).format(Message::Error, &[&error]));
+            std::process::exit(1);
+        }
+    };
+    let locale = Locale::detect(config.locale);
+    let filter = match args.filter(config.calendar, locale) {
+        Ok(filter) => filter,
+        Err(error) => {
+            eprintln!("{}", locale.format(Message::Error, &[&error]));
+            std::process::exit(1);
+        }
+    };
+    if let Some(Command::Config) = args.command {
+        print!("{}", config.to_toml());
+        return Ok(());
+    }
     let is_cli = args.is_cli();
-    let mut app = App::new(args)?;
+    let command = args.command.take();
+    let mut app = App::new(args, config)?;
+    app.set_filter(filter);
     if is_cli {
-        cli::run(&mut app)?;
+        cli::run(&mut app, command)?;
     } else {
         tui::run(&mut app)?;
     }
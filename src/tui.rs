@@ -1,12 +1,30 @@
-use std::io::{self, stdout};
+use std::{collections::HashMap, env, fmt, fs, ops::Range, path::{Path, PathBuf}, process, time::{Duration, Instant}};
+use std::io::{self, stdout, Write};
 use ratatui::{prelude::*, widgets::*};
-use tui_textarea::{Input, TextArea, Key};
+use tui_textarea::{CursorMove, Input, TextArea, Key};
+use similar::{ChangeTag, TextDiff};
+use regex::{Regex, RegexBuilder};
+use unicode_width::UnicodeWidthStr;
+use chrono::{Days, NaiveDate};
 use crossterm::{
     ExecutableCommand,
     terminal::{disable_raw_mode, LeaveAlternateScreen, enable_raw_mode, EnterAlternateScreen},
 };
 
-use crate::app::App;
+use crate::app::{App, AppError};
+use crate::app::file::{self, date::{self, DateFilter, Period}};
+use crate::app::file::drafts::Drafts;
+use crate::app::preview::Previewer;
+use crate::app::autosave::Autosaver;
+use crate::app::stats::{Series, Writing};
+use crate::app::summary::{self, fields, split_front_matter};
+use crate::config::{Action, Field, Sort};
+use crate::locale::{Locale, Message};
+
+mod bidi;
+mod calendar;
+mod markdown;
+mod stats;
 
 pub fn default_block<'a, T>(title: T) -> Block<'a> 
 where
@@ -15,11 +33,6 @@ where
     Block::default().title(title).borders(Borders::ALL)
 }
 
-pub enum DirectoryWidget<'a> {
-    List(ratatui::widgets::List<'a>),
-    Paragraph(ratatui::widgets::Paragraph<'a>),
-}
-
 /// Shutdown TUI app (undo everything did in startup, and show cursor)
 pub fn shutdown() -> io::Result<()> {
     disable_raw_mode()?;
@@ -48,11 +61,60 @@ enum TuiMode {
     List,
     TextEditor,
     Pager,
+    /// Typing a search of the pager, in its status line
+    PagerSearch,
+    Filter,
+    Search,
+    Confirm,
+    History,
+    /// Heatmap of the days entries were written on
+    Calendar,
+    /// Offering to recover drafts autosaved by editors that weren't closed
+    Drafts,
+    /// Tag sidebar next to the list, which shows the entries with the selected tag
+    Tags,
+    /// Typing a new name for the selected tag
+    RenameTag,
+    /// Asking for the configured fields an entry doesn't have before saving it
+    FieldPrompt,
+    /// Charts of numeric fields over time
+    Stats,
+}
+
+/// How often the builtin editor checks whether its draft is due for an autosave
+const AUTOSAVE_TICK: Duration = Duration::from_secs(1);
+const MIN_LIST_WIDTH: u16 = 10;
+const MAX_LIST_WIDTH: u16 = 90;
+/// How often the interface checks for previews decrypted in the background
+const PREVIEW_TICK: Duration = Duration::from_millis(50);
+/// How many entries above and below the selection are decrypted for their summaries in the list
+const PREVIEW_NEIGHBOURS: usize = 10;
+/// Actions of the calendar, which take precedence over the list's keys there
+const CALENDAR_ACTIONS: [Action; 9] = [
+    Action::PreviousDay, Action::NextDay, Action::PreviousMonth, Action::NextMonth,
+    Action::PreviousYear, Action::NextYear, Action::Today, Action::CalendarView, Action::CalendarWords,
+];
+
+/// Actions waiting for the user to confirm them in `TuiMode::Confirm`
+enum Confirm {
+    Delete(String),
+    Discard,
+    DiscardDraft(String),
+    /// Saving the entry `name` with all its text removed, which deletes it or discards the change
+    DeleteEmptied(String),
+}
+
+impl Confirm {
+    /// Whether the action can be done more thoroughly with `s`
+    fn can_shred(&self) -> bool {
+        matches!(self, Confirm::Delete(_))
+    }
 }
 
 enum TextMode {
     Add,
-    Edit,
+    /// Editing the entry with this name
+    Edit(String),
 }
 pub struct TuiApp<'a>{
     mode: TuiMode,
@@ -61,8 +123,71 @@ pub struct TuiApp<'a>{
     max_scroll: u16,
     index: usize,
     text_mode: TextMode,
+    /// Text of the entry as of the last save, to tell whether the editor has unsaved changes
+    saved_text: String,
+    /// Draft the builtin editor autosaves to, with the text last written to it and when
+    draft: Option<String>,
+    autosaved_text: String,
+    autosaved_at: Instant,
+    /// Drafts found when the journal was unlocked, while going through them in `TuiMode::Drafts`
+    drafts: Vec<String>,
+    draft_index: usize,
+    previewer: Option<Previewer>,
+    autosaver: Option<Autosaver>,
+    /// Day selected in `TuiMode::Calendar`, whether the whole year is shown, and whether days are
+    /// shaded by words written instead of entries
+    calendar_day: NaiveDate,
+    calendar_year: bool,
+    calendar_words: bool,
+    /// Entries and words written each day, from when the calendar was opened or entries were last
+    /// decrypted in it
+    activity: HashMap<NaiveDate, (usize, usize)>,
+    /// Tags with their entry counts while browsing them in `TuiMode::Tags`, and the tag the list
+    /// was filtered by before, to go back to
+    tags: Vec<(String, usize)>,
+    tag_index: usize,
+    previous_tag: Option<String>,
+    /// Fields still to ask for before saving, last first, what's being typed for the current one,
+    /// whether they were asked for already while editing this entry, and whether the editor closes
+    /// once they're all answered
+    field_prompts: Vec<Field>,
+    field_input: TextArea<'a>,
+    fields_asked: bool,
+    close_after_fields: bool,
+    /// Why the editor's text couldn't be saved, until it's edited again
+    editor_error: Option<String>,
+    /// Why the last action failed, shown over the screen until the next key
+    error: Option<String>,
+    /// Writing statistics and numeric fields shown in `TuiMode::Stats`, and the field charted
+    writing: Writing,
+    series: Series,
+    series_index: usize,
+    show_preview: bool,
+    /// Percentage of the width taken by the list when the preview is shown
+    list_width: u16,
     pager_scroll: u16,
     content: String,
+    /// `content` rendered for the pager, and the width it was wrapped to
+    pager_lines: Vec<Line<'static>>,
+    pager_width: u16,
+    /// The selected entry rendered for the preview pane, and the content and width it's from
+    preview_lines: Vec<Line<'static>>,
+    preview_content: String,
+    preview_width: u16,
+    pager_height: u16,
+    /// Pager search, whether it goes upwards, and its matches as line and byte range in the line
+    pager_search: Option<Regex>,
+    search_backward: bool,
+    pager_matches: Vec<(usize, Range<usize>)>,
+    pager_match: Option<usize>,
+    query: String,
+    confirm: Option<Confirm>,
+    /// Entries deleted in this session, most recent last, for undoing
+    deleted: Vec<String>,
+    /// Revisions of the selected entry, newest first, while browsing them in `TuiMode::History`
+    revisions: Vec<String>,
+    revision_index: usize,
+    diff: Vec<Line<'static>>,
 }
 
 pub enum Operation {
@@ -77,11 +202,53 @@ impl <'a>TuiApp <'a>{
         let mut tui_app = TuiApp {
             max_scroll: 0,
             text_mode: TextMode::Add,
+            saved_text: String::new(),
+            draft: None,
+            autosaved_text: String::new(),
+            autosaved_at: Instant::now(),
+            drafts: vec![],
+            draft_index: 0,
+            previewer: None,
+            autosaver: None,
+            calendar_day: date::current().date_naive(),
+            calendar_year: false,
+            calendar_words: false,
+            activity: HashMap::new(),
+            tags: vec![],
+            tag_index: 0,
+            previous_tag: None,
+            field_prompts: vec![],
+            field_input: TextArea::default(),
+            fields_asked: false,
+            editor_error: None,
+            error: None,
+            close_after_fields: false,
+            writing: Writing::default(),
+            series: Series::default(),
+            series_index: 0,
+            show_preview: app.config().panes.preview,
+            list_width: app.config().panes.list_width.clamp(MIN_LIST_WIDTH, MAX_LIST_WIDTH),
             pager_scroll: 0,
             index: 0,
             mode: TuiMode::List,
             textarea,
             content: String::new(),
+            pager_lines: vec![],
+            pager_width: 0,
+            preview_lines: vec![],
+            preview_content: String::new(),
+            preview_width: 0,
+            pager_height: 0,
+            pager_search: None,
+            search_backward: false,
+            pager_matches: vec![],
+            pager_match: None,
+            query: String::new(),
+            confirm: None,
+            deleted: vec![],
+            revisions: vec![],
+            revision_index: 0,
+            diff: vec![],
             app,
         };
 
@@ -97,22 +264,88 @@ impl <'a>TuiApp <'a>{
         match mode {
             TuiMode::Password => {
                 let title = if self.app.empty() {
-                    "Initialize directory passphrase"
+                    Message::InitializePassphrase
                 } else {
-                    "Passphrase"
+                    Message::Passphrase
                 };
-                self.textarea.set_block(default_block(title));
-                self.textarea.set_style(Style::default());
+                self.textarea.set_block(self.block(self.text(title)));
+                self.textarea.set_style(self.text_style());
                 self.mask_password()
             },
             TuiMode::TextEditor => {
                 self.textarea.clear_mask_char();
-                self.textarea.set_block(default_block("Write your new journal"));
+                self.textarea.set_style(self.text_style());
             }
             TuiMode::Pager => {
                 self.content = self.app.nth_content(self.index);
+                self.pager_width = 0;
+                self.pager_scroll = 0;
+                self.pager_match = None;
+            }
+            TuiMode::PagerSearch => {
+                self.textarea = TextArea::default();
+                self.textarea.set_style(self.text_style());
+            }
+            TuiMode::Filter => {
+                self.textarea.clear_mask_char();
+                self.textarea.set_style(self.text_style());
+                self.textarea.set_block(self.block(self.text(Message::FilterByDate)));
+                let filter = self.app.filter();
+                if !filter.is_empty() {
+                    self.textarea.insert_str(filter.display(self.app.config().date_style()));
+                }
             }
-            TuiMode::List =>  {}
+            TuiMode::Search => {
+                self.textarea = TextArea::default();
+                self.textarea.set_style(self.text_style());
+                self.textarea.set_block(self.block(self.text(Message::Search)));
+                self.textarea.insert_str(&self.query);
+            }
+            TuiMode::History => {
+                let name = self.selected_name();
+                self.revisions = self.app.revisions(&name).unwrap_or_default();
+                self.revision_index = 0;
+                self.pager_scroll = 0;
+                self.update_diff();
+            }
+            TuiMode::Calendar => {
+                if let Some(created) = date::parse(&self.selected_name()) {
+                    self.calendar_day = created.date_naive();
+                }
+                self.activity = self.app.activity();
+            }
+            TuiMode::Drafts => {
+                self.drafts = self.app.drafts().unwrap_or_default();
+                self.draft_index = self.draft_index.min(self.drafts.len().saturating_sub(1));
+                self.pager_scroll = 0;
+                self.update_draft_diff();
+            }
+            TuiMode::Tags => {
+                self.tags = self.app.tags();
+                let tag = self.app.tag().map(str::to_string);
+                self.tag_index = self.tags.iter().position(|(name, _)| Some(name) == tag.as_ref()).unwrap_or(0);
+                self.previous_tag = tag;
+                self.select_tag();
+            }
+            TuiMode::RenameTag => {
+                let tag = self.tags.get(self.tag_index).map(|(tag, _)| tag.clone()).unwrap_or_default();
+                self.textarea = TextArea::default();
+                self.textarea.set_style(self.text_style());
+                self.textarea.set_block(self.block(self.app.locale().format(Message::RenameTag, &[&tag])));
+                self.textarea.insert_str(tag);
+            }
+            TuiMode::FieldPrompt => {
+                let label = self.field_prompts.last().map(Field::label).unwrap_or_default();
+                self.field_input = TextArea::default();
+                self.field_input.set_style(self.text_style());
+                self.field_input.set_block(self.block(self.app.locale().format(Message::FieldPrompt, &[&label])));
+            }
+            TuiMode::Stats => {
+                self.writing = self.app.writing().unwrap_or_default();
+                self.series = self.app.series().unwrap_or_default();
+                self.series_index = self.series_index.min(self.series.fields.len().saturating_sub(1));
+            }
+            TuiMode::List | TuiMode::Confirm =>  {}
         }
         self.mode = mode;
     }
@@ -126,35 +359,817 @@ impl <'a>TuiApp <'a>{
                 frame.render_widget(self.textarea.widget(),centered_rect(frame.size(), 35, 3));
             }
             TuiMode::TextEditor => {
+                self.render_editor(frame);
+            }
+            TuiMode::List => self.render_list(frame, frame.size(), list_state),
+            TuiMode::Search => {
+                let layout = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)])
+                    .split(frame.size());
+                self.render_list(frame, layout[0], list_state);
+                frame.render_widget(self.textarea.widget(), layout[1]);
+            }
+            TuiMode::History => {
+                let layout = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Length(26), Constraint::Min(0)])
+                    .split(frame.size());
+                let format = self.app.config().date_format.clone();
+                let style = self.app.config().date_style();
+                let items: Vec<String> = self.revisions.iter()
+                    .map(|revision| date::display(revision.split('.').next().unwrap_or(revision), &format, style))
+                    .collect();
+                let mut revision_state = ListState::default();
+                revision_state.select(Some(self.revision_index));
+                let list = List::new(items)
+                    .block(self.block(self.text(Message::Revisions)))
+                    .style(self.text_style())
+                    .highlight_style(self.highlight_style());
+                frame.render_stateful_widget(list, layout[0], &mut revision_state);
+                let title = if self.revisions.is_empty() {
+                    self.text(Message::NoRevisions).to_string()
+                } else {
+                    self.hint(Message::ChangesSinceRevision, &[Action::ScrollDown, Action::ScrollUp, Action::Restore])
+                };
+                let paragraph = Paragraph::new(self.diff.clone())
+                    .block(self.block(title))
+                    .style(self.text_style())
+                    .scroll((self.pager_scroll, 0));
+                frame.render_widget(paragraph, layout[1]);
+            }
+            TuiMode::Drafts => self.render_drafts(frame),
+            TuiMode::Calendar => self.render_calendar(frame),
+            TuiMode::Confirm => {
+                let question = match &self.confirm {
+                    Some(Confirm::Delete(name)) => {
+                        let name = name.clone();
+                        self.render_list(frame, frame.size(), list_state);
+                        let date = date::display(&name, &self.app.config().date_format, self.app.config().date_style());
+                        let keys = &self.app.config().keys;
+                        let (yes, no, shred) = (keys.hint(Action::Yes), keys.hint(Action::No), keys.hint(Action::Shred));
+                        self.app.locale().format(Message::ConfirmDelete, &[&date, &yes, &no, &shred])
+                    }
+                    Some(Confirm::Discard) => {
+                        self.render_editor(frame);
+                        self.hint(Message::ConfirmDiscard, &[Action::Yes, Action::No])
+                    }
+                    Some(Confirm::DiscardDraft(_)) => {
+                        self.render_drafts(frame);
+                        self.hint(Message::ConfirmDiscardDraft, &[Action::Yes, Action::No])
+                    }
+                    Some(Confirm::DeleteEmptied(_)) => {
+                        self.render_editor(frame);
+                        self.hint(Message::ConfirmDeleteEmptied, &[Action::Yes, Action::No])
+                    }
+                    None => String::new(),
+                };
+                let area = text_rect(frame.size(), 50, &question);
+                let paragraph = Paragraph::new(question)
+                    .style(self.text_style())
+                    .wrap(Wrap { trim: true })
+                    .block(self.block(self.text(Message::Confirm)));
+                frame.render_widget(Clear, area);
+                frame.render_widget(paragraph, area);
+            }
+            TuiMode::Filter => {
+                self.render_list(frame, frame.size(), list_state);
+                let area = centered_rect(frame.size(), 50, 3);
+                frame.render_widget(Clear, area);
+                frame.render_widget(self.textarea.widget(), area);
+            }
+            TuiMode::Tags => self.render_tags(frame, list_state),
+            TuiMode::RenameTag => {
+                self.render_tags(frame, list_state);
+                let area = centered_rect(frame.size(), 50, 3);
+                frame.render_widget(Clear, area);
+                frame.render_widget(self.textarea.widget(), area);
+            }
+            TuiMode::FieldPrompt => {
+                self.render_editor(frame);
+                let area = centered_rect(frame.size(), 50, 3);
+                frame.render_widget(Clear, area);
+                frame.render_widget(self.field_input.widget(), area);
+            }
+            TuiMode::Stats => self.render_stats(frame),
+            TuiMode::Pager | TuiMode::PagerSearch => self.render_pager(frame),
+        }
+        self.render_error(frame);
+    }
 
-                self.textarea.clear_mask_char();
-                frame.render_widget(self.textarea.widget(),frame.size());
+    /// The error of the last action, if any, in a popup over the screen.
+    fn render_error(&self, frame: &mut Frame) {
+        let Some(error) = &self.error else {
+            return
+        };
+        let text = self.app.locale().format(Message::Error, &[error]);
+        let area = text_rect(frame.size(), 60, &text);
+        let paragraph = Paragraph::new(text)
+            .style(self.text_style())
+            .wrap(Wrap { trim: true })
+            .block(self.block(""));
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+
+    /// Writing statistics beside charts of the numeric fields.
+    fn render_stats(&mut self, frame: &mut Frame) {
+        let layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(38), Constraint::Min(0)])
+            .split(frame.size());
+        self.render_writing(frame, layout[0]);
+        self.render_fields(frame, layout[1]);
+    }
+
+    /// Totals and streaks, and bars of the entries written on each weekday, in each hour and in
+    /// each of the last months.
+    fn render_writing(&mut self, frame: &mut Frame, area: Rect) {
+        let locale = self.app.locale();
+        let style = self.app.config().date_style();
+        let calendar = self.app.config().calendar;
+        let writing = &self.writing;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(7), Constraint::Length(10), Constraint::Length(4), Constraint::Min(6)])
+            .split(area);
+        let totals: Vec<Line> = [
+            locale.format(Message::TotalEntries, &[&writing.entries]),
+            locale.format(Message::TotalWords, &[&writing.words]),
+            locale.format(Message::AverageLength, &[&writing.average()]),
+            locale.format(Message::LongestStreak, &[&writing.longest_streak]),
+            locale.format(Message::CurrentStreak, &[&writing.current_streak]),
+        ].into_iter().map(Line::raw).collect();
+        let paragraph = Paragraph::new(totals)
+            .block(self.block(locale.text(Message::Writing)))
+            .style(self.text_style());
+        frame.render_widget(paragraph, layout[0]);
+        let bar_style = Style::new().fg(self.app.config().theme.tag);
+
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let labels: Vec<String> = (0..7).map(|i| style.format_day(monday + Days::new(i), "%a")).collect();
+        let weekdays: Vec<(&str, u64)> = labels.iter().zip(writing.weekdays)
+            .map(|(label, count)| (label.as_str(), count as u64))
+            .collect();
+        let chart = BarChart::default()
+            .data(&weekdays)
+            .bar_width(4)
+            .bar_gap(1)
+            .bar_style(bar_style)
+            .block(self.block(locale.text(Message::ByWeekday)))
+            .style(self.text_style());
+        frame.render_widget(chart, layout[1]);
+
+        let hours: Vec<u64> = writing.hours.iter().map(|count| *count as u64).collect();
+        let sparkline = Sparkline::default()
+            .data(&hours)
+            .style(bar_style)
+            .block(self.block(format!("{} 0–23", locale.text(Message::ByHour))));
+        frame.render_widget(sparkline, layout[2]);
+
+        // The months that fit, up to the current one, including those without entries
+        let fit = (layout[3].width.saturating_sub(2) / 5).max(1) as i32;
+        let current = calendar.first_of_month(date::current().date_naive());
+        let months: Vec<(String, u64)> = (1 - fit..=0)
+            .filter_map(|offset| calendar.add_months(current, offset))
+            .map(|first| {
+                let entries = writing.months.get(&first).map_or(0, |(entries, _)| *entries);
+                (style.format_day(first, "%b"), entries as u64)
+            })
+            .collect();
+        let months: Vec<(&str, u64)> = months.iter().map(|(label, entries)| (label.as_str(), *entries)).collect();
+        let chart = BarChart::default()
+            .data(&months)
+            .bar_width(4)
+            .bar_gap(1)
+            .bar_style(bar_style)
+            .block(self.block(locale.text(Message::ByMonth)))
+            .style(self.text_style());
+        frame.render_widget(chart, layout[3]);
+    }
+
+    /// A chart of the selected field over time, above a sparkline of the latest values of each.
+    fn render_fields(&mut self, frame: &mut Frame, area: Rect) {
+        let locale = self.app.locale();
+        if self.series.fields.is_empty() {
+            let paragraph = Paragraph::new(locale.text(Message::NoFields))
+                .block(self.block(locale.text(Message::Stats)))
+                .style(self.text_style());
+            frame.render_widget(paragraph, area);
+            return
+        }
+        let shown = (area.height.saturating_sub(10) / 3).max(1) as usize;
+        let first = self.series_index.saturating_sub(shown - 1);
+        let fields = &self.series.fields[first..(first + shown).min(self.series.fields.len())];
+        let mut constraints = vec![Constraint::Min(7)];
+        constraints.extend(fields.iter().map(|_| Constraint::Length(3)));
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+        let configured = |name: &str| self.app.config().fields.iter().find(|field| field.name == name);
+        let selected = &self.series.fields[self.series_index];
+        let trend = stats::Trend::new(&self.series.values(selected), configured(selected));
+        let label = configured(selected).map_or(selected.clone(), Field::label);
+        let title = format!("{}: {} ({})", locale.text(Message::Stats), label, self.hint(Message::StatsHint, &[Action::Down, Action::Up]));
+        let chart = trend.chart(self.app.config().date_style())
+            .block(self.block(title))
+            .style(self.text_style());
+        frame.render_widget(chart, layout[0]);
+        for (i, field) in fields.iter().enumerate() {
+            let trend = stats::Trend::new(&self.series.values(field), configured(field));
+            let title = match self.series.summary(field) {
+                Some(summary) => locale.format(Message::FieldSummary, &[
+                    field, &summary.count, &stats::number(summary.min), &format!("{:.1}", summary.mean),
+                    &stats::number(summary.max), &stats::number(summary.last),
+                ]),
+                None => field.clone(),
+            };
+            let area = layout[i + 1];
+            let bars = trend.bars(area.width.saturating_sub(2) as usize);
+            let mut block = self.block(title);
+            if first + i == self.series_index {
+                block = block.border_style(Style::new().fg(self.app.config().theme.tag));
             }
-            TuiMode::List => {
-                let list = 
-                    List::new(self.app.entries())
-                    .block(default_block("Journals"))
-                    .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+            let sparkline = Sparkline::default()
+                .data(&bars)
+                .max(stats::BAR_MAX)
+                .block(block)
+                .style(Style::new().fg(self.app.config().theme.tag));
+            frame.render_widget(sparkline, area);
+        }
+    }
 
-                frame.render_stateful_widget(list, frame.size(), list_state)
+    /// The tag sidebar, next to the list of the entries with the selected tag.
+    fn render_tags(&mut self, frame: &mut Frame, list_state: &mut ListState) {
+        let layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(24), Constraint::Min(0)])
+            .split(frame.size());
+        let dim = Style::new().add_modifier(Modifier::DIM);
+        let items: Vec<ListItem> = self.tags.iter()
+            .map(|(tag, count)| ListItem::new(Line::from(vec![Span::raw(format!("#{} ", tag)), Span::styled(count.to_string(), dim)])))
+            .collect();
+        let mut tag_state = ListState::default();
+        tag_state.select(Some(self.tag_index));
+        let block = self.block(self.text(Message::Tags));
+        let inner = block.inner(layout[0]);
+        let list = List::new(items)
+            .block(block)
+            .style(self.text_style())
+            .highlight_style(self.highlight_style());
+        frame.render_stateful_widget(list, layout[0], &mut tag_state);
+        bidi::reorder(frame.buffer_mut(), inner);
+        self.render_list(frame, layout[1], list_state);
+    }
+
+    fn render_pager(&mut self, frame: &mut Frame) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(frame.size());
+        let area = layout[0];
+        if self.pager_width != area.width {
+            self.pager_lines = markdown::render(&self.content, area.width);
+            self.pager_width = area.width;
+            self.update_pager_matches();
+        }
+        self.pager_height = area.height;
+        self.max_scroll = self.pager_lines.len().saturating_sub(area.height as usize).min(u16::MAX as usize) as u16;
+        if self.pager_scroll > self.max_scroll {
+            self.pager_scroll = self.max_scroll
+        }
+        let mut lines = self.pager_lines.clone();
+        for (i, (line, range)) in self.pager_matches.iter().enumerate() {
+            let style = if self.pager_match == Some(i) {
+                Style::new().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::new().add_modifier(Modifier::REVERSED)
+            };
+            lines[*line] = highlight(&lines[*line], range, style);
+        }
+        let paragraph = Paragraph::new(lines)
+            .style(self.text_style())
+            .scroll((self.pager_scroll,0));
+        frame.render_widget(paragraph, area);
+        bidi::reorder(frame.buffer_mut(), area);
+
+        if let TuiMode::PagerSearch = self.mode {
+            frame.render_widget(self.textarea.widget(), layout[1]);
+            return
+        }
+        let total = self.pager_lines.len();
+        let bottom = self.pager_scroll as usize + area.height as usize;
+        let percent = if total <= area.height as usize { 100 } else { bottom.min(total) * 100 / total };
+        let name = self.selected_name();
+        let mut status = format!(
+            " {} ({}/{})",
+            date::display(&name, &self.app.config().date_format, self.app.config().date_style()),
+            self.index + 1,
+            self.entries_len(),
+        );
+        if let Some(edited) = self.app.times(&name).and_then(|times| times.edited(self.app.locale())) {
+            status.push_str(&format!("  {}", edited));
+        }
+        if self.pager_search.is_some() {
+            match (self.pager_match, self.pager_matches.len()) {
+                (_, 0) => status.push_str(&format!("  {}", self.text(Message::NoMatches))),
+                (Some(current), matches) => status.push_str(&format!("  {}", self.app.locale().format(Message::Match, &[&(current + 1), &matches]))),
+                (None, matches) => status.push_str(&format!("  {}", self.app.locale().format(Message::Matches, &[&matches]))),
             }
-            TuiMode::Pager => {
-                self.max_scroll = match self.content.lines().count() as i32 - frame.size().height as i32 {
-                    ..=0 => 0,
-                    any => any as u16,
+        }
+        let percent = format!("{}% ", percent);
+        let padding = (layout[1].width as usize).saturating_sub(status.width() + percent.len());
+        let status = Paragraph::new(format!("{}{}{}", status, " ".repeat(padding), percent))
+            .style(self.text_style().add_modifier(Modifier::REVERSED));
+        frame.render_widget(status, layout[1]);
+    }
+
+    /// Find the pager search in the rendered lines.
+    fn update_pager_matches(&mut self) {
+        self.pager_matches = vec![];
+        if let Some(search) = &self.pager_search {
+            for (i, line) in self.pager_lines.iter().enumerate() {
+                let text: String = line.spans.iter().map(|span| span.content.as_ref()).collect();
+                self.pager_matches.extend(search.find_iter(&text)
+                    .filter(|found| !found.is_empty())
+                    .map(|found| (i, found.range())));
+            }
+        }
+        if self.pager_match.is_some_and(|current| current >= self.pager_matches.len()) {
+            self.pager_match = None;
+        }
+    }
+
+    /// Search the pager for `pattern`, case insensitively unless it has uppercase letters, and go
+    /// to the first match below the top of the page, or above it when searching backward.
+    fn on_pager_search(&mut self, pattern: &str) {
+        self.pager_match = None;
+        self.pager_search = if pattern.is_empty() {
+            None
+        } else {
+            RegexBuilder::new(&regex::escape(pattern))
+                .case_insensitive(!pattern.chars().any(char::is_uppercase))
+                .build()
+                .ok()
+        };
+        self.update_pager_matches();
+        self.next_match(self.search_backward);
+    }
+
+    /// Go to the next match, or the previous one with `backward`, wrapping around.
+    fn next_match(&mut self, backward: bool) {
+        if self.pager_matches.is_empty() {
+            return
+        }
+        let top = self.pager_scroll as usize;
+        let last = self.pager_matches.len() - 1;
+        let next = match (self.pager_match, backward) {
+            (Some(current), false) => if current == last { 0 } else { current + 1 },
+            (Some(current), true) => if current == 0 { last } else { current - 1 },
+            (None, false) => self.pager_matches.iter().position(|(line, _)| *line >= top).unwrap_or(0),
+            (None, true) => self.pager_matches.iter().rposition(|(line, _)| *line < top).unwrap_or(last),
+        };
+        self.pager_match = Some(next);
+        let line = self.pager_matches[next].0;
+        if line < top || line >= top + self.pager_height as usize {
+            self.pager_scroll = line.min(self.max_scroll as usize) as u16;
+        }
+    }
+
+    /// Open the entry `offset` away from the current one in the pager, keeping the search.
+    fn pager_entry(&mut self, offset: isize) {
+        let index = self.index as isize + offset;
+        if index >= 0 && (index as usize) < self.entries_len() {
+            self.index = index as usize;
+            self.set_mode(TuiMode::Pager);
+        }
+    }
+
+    fn render_editor(&mut self, frame: &mut Frame) {
+        let title = match self.text_mode {
+            TextMode::Add => self.text(Message::NewJournal),
+            TextMode::Edit(_) => self.text(Message::EditJournal),
+        };
+        let title = if self.is_modified() {
+            self.app.locale().format(Message::Modified, &[&title])
+        } else {
+            title.to_string()
+        };
+        self.textarea.clear_mask_char();
+        let block = match &self.editor_error {
+            Some(error) => self.block(format!("{} · {}", title, error)).border_style(self.error_style()),
+            None => self.block(title),
+        };
+        let inner = block.inner(frame.size());
+        self.textarea.set_block(block);
+        frame.render_widget(self.textarea.widget(),frame.size());
+        bidi::reorder(frame.buffer_mut(), inner);
+    }
+
+    /// Move the editor's cursor a grapheme to the `right` or left as it's shown, which in
+    /// right-to-left text is backwards. Past the end of the line it moves to the next or previous
+    /// line, like the textarea does.
+    fn move_cursor_visually(&mut self, right: bool) {
+        let (row, column) = self.textarea.cursor();
+        let line = self.textarea.lines()[row].clone();
+        // The cursor after the end of the line is shown on a space of its own
+        let mut clusters = bidi::clusters(&line);
+        clusters.push(" ");
+        let mut chars = 0;
+        let current = clusters.iter()
+            .position(|cluster| {
+                chars += cluster.chars().count();
+                chars > column
+            })
+            .unwrap_or(clusters.len() - 1);
+        let visual = bidi::Visual::new(&clusters);
+        let position = visual.order.iter().position(|&i| i == current).unwrap_or(0);
+        let target = if right { position.checked_add(1) } else { position.checked_sub(1) };
+        match target.and_then(|target| visual.order.get(target)) {
+            Some(&target) => {
+                let column: usize = clusters[..target].iter().map(|cluster| cluster.chars().count()).sum();
+                self.textarea.move_cursor(CursorMove::Jump(row as u16, column as u16));
+            }
+            // Off the start or the end of the line, whichever side that's shown on
+            None if clusters.len() == 1 && !right => self.textarea.move_cursor(CursorMove::Back),
+            None if current == clusters.len() - 1 => self.textarea.move_cursor(CursorMove::Forward),
+            None if current == 0 => self.textarea.move_cursor(CursorMove::Back),
+            None => {}
+        }
+    }
+
+    fn render_calendar(&mut self, frame: &mut Frame) {
+        self.start_previewer();
+        self.receive_previews();
+        let style = self.app.config().date_style();
+        let heatmap = calendar::Heatmap::new(self.activity.clone(), self.calendar_words, style, self.app.locale());
+        let area = frame.size();
+        let (mut lines, width) = if self.calendar_year {
+            let columns = (area.width.saturating_sub(2) as usize / (calendar::MONTH_WIDTH + 2)).clamp(1, 4);
+            (heatmap.year(self.calendar_day, columns), columns * (calendar::MONTH_WIDTH + 2))
+        } else {
+            (heatmap.month(self.calendar_day, self.calendar_day), calendar::MONTH_WIDTH)
+        };
+        let (entries, words) = heatmap.day(self.calendar_day);
+        let day = style.format_day(self.calendar_day, "%a %-d %b %Y");
+        lines.push(Line::raw(""));
+        let locale = self.app.locale();
+        lines.push(Line::raw(match entries {
+            0 => locale.format(Message::DayNoEntries, &[&day]),
+            1 => locale.format(Message::DayOneEntry, &[&day, &words]),
+            entries => locale.format(Message::DayEntries, &[&day, &entries, &words]),
+        }));
+        let keys = &self.app.config().keys;
+        let moves = self.hint(Message::CalendarMoveHint, &[
+            Action::PreviousDay, Action::NextDay, Action::Up, Action::Down, Action::PreviousMonth,
+            Action::NextMonth, Action::PreviousYear, Action::NextYear, Action::Today,
+        ]);
+        let hint = locale.format(Message::CalendarHint, &[
+            &keys.hint(Action::CalendarView),
+            &keys.hint(Action::CalendarWords),
+            &keys.hint_besides(Action::Open, &CALENDAR_ACTIONS),
+        ]);
+        lines.push(Line::styled(moves, Style::new().add_modifier(Modifier::DIM)));
+        lines.push(Line::styled(hint, Style::new().add_modifier(Modifier::DIM)));
+        let title = if self.calendar_words { locale.text(Message::CalendarByWords) } else { locale.text(Message::CalendarByEntries) };
+        let height = lines.len() as u16 + 2;
+        let width = lines.iter().map(Line::width).fold(width, usize::max) as u16 + 2;
+        let calendar_area = Rect {
+            x: area.x + area.width.saturating_sub(width) / 2,
+            y: area.y + area.height.saturating_sub(height) / 2,
+            width: width.min(area.width),
+            height: height.min(area.height),
+        };
+        let paragraph = Paragraph::new(lines)
+            .block(self.block(title))
+            .style(self.text_style());
+        frame.render_widget(paragraph, calendar_area);
+        bidi::reorder(frame.buffer_mut(), calendar_area.inner(&Margin::new(1, 1)));
+    }
+
+    /// Move the calendar selection to `day`, when it's a valid date.
+    fn move_calendar(&mut self, day: Option<NaiveDate>) {
+        if let Some(day) = day {
+            self.calendar_day = day;
+        }
+    }
+
+    /// List the entries of the selected day, or open the entry right away when there's one.
+    fn on_calendar_day(&mut self) {
+        let Some(period) = Period::day(self.calendar_day) else {
+            return
+        };
+        self.app.set_filter(DateFilter::new(None, None, Some(period)));
+        self.go_top();
+        match self.entries_len() {
+            1 => self.set_mode(TuiMode::Pager),
+            _ => self.set_mode(TuiMode::List),
+        }
+    }
+
+    fn render_drafts(&mut self, frame: &mut Frame) {
+        let layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(30), Constraint::Min(0)])
+            .split(frame.size());
+        let format = self.app.config().date_format.clone();
+        let style = self.app.config().date_style();
+        let locale = self.app.locale();
+        let items: Vec<String> = self.drafts.iter()
+            .map(|draft| match Drafts::started(draft) {
+                Some(started) => locale.format(Message::NewDraft, &[&date::display(started, &format, style)]),
+                None => locale.format(Message::EditDraft, &[&date::display(draft, &format, style)]),
+            })
+            .collect();
+        let mut draft_state = ListState::default();
+        draft_state.select(Some(self.draft_index));
+        let list = List::new(items)
+            .block(self.block(self.text(Message::UnsavedDrafts)))
+            .style(self.text_style())
+            .highlight_style(self.highlight_style());
+        frame.render_stateful_widget(list, layout[0], &mut draft_state);
+        let paragraph = Paragraph::new(self.diff.clone())
+            .block(self.block(self.hint(Message::ChangesInDraft, &[Action::ScrollDown, Action::ScrollUp, Action::Restore, Action::DiscardDraft])))
+            .style(self.text_style())
+            .scroll((self.pager_scroll, 0));
+        frame.render_widget(paragraph, layout[1]);
+    }
+
+    fn render_list(&mut self, frame: &mut Frame, area: Rect, list_state: &mut ListState) {
+        self.start_previewer();
+        self.receive_previews();
+        self.request_previews();
+        let area = if self.show_preview {
+            let layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(self.list_width), Constraint::Min(0)])
+                .split(area);
+            self.render_preview(frame, layout[1]);
+            layout[0]
+        } else {
+            area
+        };
+        let filter = self.app.filter();
+        let today = date::current().date_naive();
+        let mut title = match self.app.sort() {
+            _ if self.app.on_this_day() => {
+                let day = self.app.config().date_style().format_day(today, "%-d %B");
+                self.app.locale().format(Message::OnThisDay, &[&day])
+            }
+            Sort::Created => self.text(Message::Journals).to_string(),
+            Sort::Modified => self.text(Message::JournalsByLastEdit).to_string(),
+        };
+        if !filter.is_empty() {
+            title = format!("{} ({})", title, filter.display(self.app.config().date_style()));
+        }
+        if let Some(tag) = self.app.tag() {
+            title = format!("{} #{}", title, tag);
+        }
+        if !self.query.is_empty() {
+            title = format!("{} /{}", title, self.query);
+        }
+        let highlight_style = self.highlight_style();
+        let format = self.app.config().date_format.clone();
+        let style = self.app.config().date_style();
+        let locale = self.app.locale();
+        let tag_style = Style::new().fg(Color::Black).bg(self.app.config().theme.tag);
+        // Entries from earlier years on this day are grouped under their year
+        let this_year = style.calendar.ymd(today).0;
+        let mut last_year = None;
+        let on_this_day = self.app.on_this_day();
+        let items: Vec<ListItem> = self.app.entries().iter()
+            .map(|name| {
+                let mut lines = vec![];
+                let year = date::parse(name).map(|created| style.calendar.ymd(created.date_naive()).0);
+                if on_this_day && year != last_year {
+                    if let Some(year) = year {
+                        let header = date::years_ago(locale, style, year, this_year);
+                        lines.push(Line::styled(header, Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)));
+                    }
+                    last_year = year;
+                }
+                let date = Span::raw(date::display(name, &format, style));
+                let edited = self.app.times(name).and_then(|times| times.edited(self.app.locale()));
+                let Some(summary) = self.app.summary(name) else {
+                    lines.push(Line::from(date));
+                    return ListItem::new(lines)
+                };
+                let words = match summary.words {
+                    1 => format!("  {}", locale.format(Message::Word, &[&1])),
+                    words => format!("  {}", locale.format(Message::Words, &[&words])),
+                };
+                let words = match edited {
+                    Some(edited) => format!("{}, {}", words, edited),
+                    None => words,
                 };
-                if self.pager_scroll > self.max_scroll {
-                    self.pager_scroll = self.max_scroll
+                let words = Span::styled(words, Style::new().add_modifier(Modifier::DIM));
+                let mut title = vec![Span::styled(summary.title.clone(), Style::new().add_modifier(Modifier::BOLD))];
+                for tag in &summary.tags {
+                    title.push(Span::raw(" "));
+                    title.push(Span::styled(format!(" {} ", tag), tag_style));
                 }
-                let paragraph = Paragraph::new(self.content.clone()).scroll((self.pager_scroll,0));
-                frame.render_widget(paragraph, frame.size());
+                lines.push(Line::from(vec![date, words]));
+                lines.push(Line::from(title));
+                ListItem::new(lines)
+            })
+            .collect();
+        let block = self.block(title);
+        let inner = block.inner(area);
+        let list = 
+            List::new(items)
+            .block(block)
+            .style(self.text_style())
+            .highlight_style(highlight_style);
+
+        frame.render_stateful_widget(list, area, list_state);
+        bidi::reorder(frame.buffer_mut(), inner);
+    }
+
+    /// The selected entry, decrypted in the background the first time it's shown.
+    fn render_preview(&mut self, frame: &mut Frame, area: Rect) {
+        self.receive_previews();
+        let name = self.selected_name();
+        let block = self.block(self.text(Message::Preview));
+        let paragraph = if name.is_empty() {
+            Paragraph::new("")
+        } else if let Some(content) = self.app.cached_content(&name) {
+            let width = area.width.saturating_sub(2);
+            if self.preview_width != width || self.preview_content != content {
+                self.preview_lines = markdown::render(content, width);
+                self.preview_content = content.to_string();
+                self.preview_width = width;
+            }
+            Paragraph::new(self.preview_lines.clone()).style(self.text_style())
+        } else if let Some(error) = self.previewer.as_ref().and_then(|previewer| previewer.error(&name)) {
+            Paragraph::new(error.to_string()).style(self.error_style())
+        } else {
+            Paragraph::new(self.text(Message::Decrypting)).style(self.text_style())
+        };
+        let inner = block.inner(area);
+        let paragraph = paragraph.block(block).wrap(Wrap { trim: false });
+        frame.render_widget(paragraph, area);
+        bidi::reorder(frame.buffer_mut(), inner);
+    }
+
+    /// Start decrypting entries in the background once there's a passphrase.
+    fn start_previewer(&mut self) {
+        if self.previewer.is_none() {
+            self.previewer = self.app.previewer();
+        }
+    }
+
+    /// Decrypt the selected entry in the background, then the entries around it for their
+    /// summaries in the list.
+    fn request_previews(&mut self) {
+        if self.previewer.is_none() {
+            return
+        }
+        let entries = self.app.entries();
+        let mut wanted: Vec<(String, PathBuf)> = vec![];
+        for distance in 0..=PREVIEW_NEIGHBOURS {
+            for index in [self.index.checked_add(distance), self.index.checked_sub(distance)].into_iter().flatten() {
+                let Some(name) = entries.get(index) else {
+                    continue
+                };
+                if self.app.cached_content(name).is_none() && !wanted.iter().any(|(wanted, _)| wanted == name) {
+                    wanted.push((name.clone(), self.app.entry_path(name)));
+                }
+            }
+        }
+        if let Some(previewer) = &mut self.previewer {
+            previewer.request(wanted);
+        }
+    }
+
+    fn receive_previews(&mut self) {
+        let Some(previewer) = &mut self.previewer else {
+            return
+        };
+        let received = previewer.receive();
+        if received.is_empty() {
+            return
+        }
+        // Decrypted entries can move in the list when it's sorted by modification time
+        let selected = self.selected_name();
+        for (name, entry) in received {
+            self.app.cache_entry(name, &entry);
+        }
+        // Decrypted entries count their words in the calendar
+        if matches!(self.mode, TuiMode::Calendar) {
+            self.activity = self.app.activity();
+        }
+        if self.app.sort() == Sort::Modified {
+            self.select(&selected);
+        }
+    }
+
+    /// `default_block` with the border color of the theme
+    #[inline]
+    fn block<'b, T: Into<Line<'b>>>(&self, title: T) -> Block<'b> {
+        default_block(bidi::visual_line(title.into())).border_style(Style::default().fg(self.app.config().theme.border))
+    }
+
+    #[inline]
+    fn highlight_style(&self) -> Style {
+        match self.app.config().theme.selected {
+            Some(color) => Style::new().bg(color),
+            None => Style::new().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    #[inline]
+    fn text(&self, message: Message) -> &'static str {
+        self.app.locale().text(message)
+    }
+
+    /// `message` with the first key bound to each of `actions`, for hints like `r to restore`.
+    fn hint(&self, message: Message, actions: &[Action]) -> String {
+        let keys: Vec<String> = actions.iter().map(|action| self.app.config().keys.hint(*action)).collect();
+        let args: Vec<&dyn fmt::Display> = keys.iter().map(|key| key as &dyn fmt::Display).collect();
+        self.app.locale().format(message, &args)
+    }
+
+    #[inline]
+    fn text_style(&self) -> Style {
+        Style::default().fg(self.app.config().theme.text)
+    }
+
+    #[inline]
+    fn error_style(&self) -> Style {
+        Style::default().fg(self.app.config().theme.error)
+    }
+
+    #[inline]
+    fn selected_name(&mut self) -> String {
+        self.app.entries().get(self.index).cloned().unwrap_or_default()
+    }
+
+    /// Diff the selected revision against the current version of the entry.
+    fn update_diff(&mut self) {
+        let name = self.selected_name();
+        self.diff = match self.revisions.get(self.revision_index) {
+            Some(revision) => {
+                let old = self.app.revision_content(&name, revision).unwrap_or_default();
+                let new = self.app.content(&name).unwrap_or_default();
+                diff_lines(&old, &new)
+            }
+            None => vec![],
+        };
+    }
+
+    /// Diff the selected draft against the entry it edits.
+    fn update_draft_diff(&mut self) {
+        self.diff = match self.drafts.get(self.draft_index) {
+            Some(draft) => {
+                let old = Drafts::entry_of(draft)
+                    .and_then(|name| self.app.content(name).ok())
+                    .unwrap_or_default();
+                let new = self.app.draft_content(draft).unwrap_or_default();
+                diff_lines(&old, &new)
             }
+            None => vec![],
+        };
+    }
+
+    /// Open the selected draft in the builtin editor, to be saved over the entry it edits, or as a
+    /// new entry when it has none.
+    fn on_recover_draft(&mut self) -> io::Result<()> {
+        let Some(draft) = self.drafts.get(self.draft_index).cloned() else {
+            return Ok(())
+        };
+        let text = self.app.draft_content(&draft)?;
+        let entry = Drafts::entry_of(&draft)
+            .and_then(|name| Some((name.to_string(), self.app.content(name).ok()?)));
+        (self.text_mode, self.saved_text) = match entry {
+            Some((name, content)) => (TextMode::Edit(name), content),
+            None => (TextMode::Add, String::new()),
+        };
+        self.diff = vec![];
+        self.textarea = TextArea::default();
+        self.set_mode(TuiMode::TextEditor);
+        self.textarea.insert_str(&text);
+        self.start_draft(draft, text);
+        Ok(())
+    }
+
+    fn on_restore_revision(&mut self) -> io::Result<()> {
+        if let Some(revision) = self.revisions.get(self.revision_index).cloned() {
+            let name = self.selected_name();
+            self.app.restore_revision(&name, &revision)?;
+            self.set_mode(TuiMode::History);
         }
+        Ok(())
+    }
+
+    #[inline]
+    fn entries_len(&mut self) -> usize {
+        self.app.entries().len()
     }
 
     #[inline]
     pub fn increment_index(&mut self) {
-        if self.index < self.app.len() - 1 {
+        if self.index + 1 < self.entries_len() {
             self.index += 1;
         } else {
             self.go_top()
@@ -177,7 +1192,7 @@ impl <'a>TuiApp <'a>{
 
     #[inline]
     pub fn go_bottom(&mut self) {
-        self.index = self.app.len() - 1
+        self.index = self.entries_len().saturating_sub(1)
     }
 
     #[inline]
@@ -190,34 +1205,424 @@ impl <'a>TuiApp <'a>{
         self.pager_scroll = 0
     }
 
-    fn on_password(&mut self) {
+    fn on_password(&mut self) -> io::Result<()> {
         let passphrase = self.textarea.lines()[0].clone();
-        self.app.set_passphrase(passphrase);
-        if self.app.test_passphrase().is_ok() {
-            self.set_mode(TuiMode::List);
-            self.textarea = TextArea::default();
+        self.app.set_passphrase(passphrase)?;
+        let title = match self.app.test_passphrase() {
+            Ok(()) => {
+                self.textarea = TextArea::default();
+                let has_drafts = self.app.drafts().is_ok_and(|drafts| !drafts.is_empty());
+                self.set_mode(if has_drafts { TuiMode::Drafts } else { TuiMode::List });
+                return Ok(())
+            }
+            Err(AppError::ExcessiveWork(required)) => self.app.locale().format(Message::WorkFactorAboveMaximum, &[&required]),
+            Err(_) => self.text(Message::WrongPassphrase).to_string(),
+        };
+        self.textarea.delete_line_by_head();
+        self.textarea.delete_line_by_end();
+        self.textarea.set_style(self.error_style());
+        self.textarea.set_block(self.block(title).style(self.error_style()));
+        Ok(())
+    }
+
+    fn on_filter(&mut self) {
+        let input = self.textarea.lines()[0].clone();
+        match DateFilter::parse(&input, self.app.config().calendar, self.app.locale()) {
+            Ok(filter) => {
+                self.app.set_filter(filter);
+                self.go_top();
+                self.textarea = TextArea::default();
+                self.set_mode(TuiMode::List);
+            }
+            Err(error) => {
+                self.textarea.set_style(self.error_style());
+                self.textarea.set_block(self.block(error).style(self.error_style()))
+            }
+        }
+    }
+
+    fn on_confirm(&mut self, shred: bool) -> io::Result<()> {
+        match self.confirm.take() {
+            Some(Confirm::Discard) => return self.close_editor(),
+            Some(Confirm::DiscardDraft(draft)) => {
+                self.discard_draft(&draft)?;
+                let remaining = self.app.drafts()?;
+                self.set_mode(if remaining.is_empty() { TuiMode::List } else { TuiMode::Drafts });
+                return Ok(())
+            }
+            Some(Confirm::DeleteEmptied(name)) => {
+                self.delete(name, false)?;
+                return self.close_editor()
+            }
+            Some(Confirm::Delete(name)) => self.delete(name, shred)?,
+            None => {}
+        }
+        self.set_mode(TuiMode::List);
+        Ok(())
+    }
+
+    /// Delete the entry `name`, keeping it for undoing when it goes to the trash.
+    fn delete(&mut self, name: String, shred: bool) -> io::Result<()> {
+        let config = self.app.config();
+        let trashed = !shred && !config.security.shred && config.trash_days > 0;
+        self.app.delete(&name, shred)?;
+        if trashed {
+            self.deleted.push(name);
+        }
+        if self.index >= self.entries_len() {
+            self.go_bottom()
+        }
+        Ok(())
+    }
+
+    /// Restore the last entry deleted in this session from the trash.
+    fn on_undo(&mut self) -> io::Result<()> {
+        if let Some(name) = self.deleted.pop() {
+            self.app.restore(&name)?;
+            self.select(&name);
+        }
+        Ok(())
+    }
+
+    /// Move the selection to the entry `name`, if it's in the list.
+    fn select(&mut self, name: &str) {
+        if let Some(index) = self.app.entries().iter().position(|entry| entry == name) {
+            self.index = index;
+        }
+    }
+
+    /// Open a random entry of the list in the pager.
+    fn open_random(&mut self) {
+        if let Some(name) = self.app.random_entry() {
+            self.select(&name);
+            self.set_mode(TuiMode::Pager);
+        }
+    }
+
+    /// Switch between listing newest written and newest edited first, keeping the selection.
+    fn toggle_sort(&mut self) {
+        let name = self.selected_name();
+        let sort = match self.app.sort() {
+            Sort::Created => Sort::Modified,
+            Sort::Modified => Sort::Created,
+        };
+        self.app.set_sort(sort);
+        self.select(&name);
+    }
+
+    /// Filter the list by the tag selected in the sidebar.
+    fn select_tag(&mut self) {
+        let tag = self.tags.get(self.tag_index).map(|(tag, _)| tag.clone());
+        self.app.set_tag(tag);
+        self.go_top();
+    }
+
+    /// Rename the selected tag to what's been typed, in every entry that has it.
+    fn on_rename_tag(&mut self) -> io::Result<()> {
+        let new = self.textarea.lines()[0].trim().trim_start_matches('#').to_string();
+        let Some((old, _)) = self.tags.get(self.tag_index).cloned() else {
+            return Ok(())
+        };
+        let renamed = !new.is_empty() && new != old;
+        // An invalid name leaves the prompt open to fix it
+        if renamed {
+            self.app.rename_tag(&old, &new)?;
+            self.app.set_tag(Some(new.clone()));
+        }
+        let mut previous_tag = self.previous_tag.take();
+        if renamed && previous_tag.as_ref() == Some(&old) {
+            previous_tag = Some(new);
+        }
+        self.textarea = TextArea::default();
+        self.set_mode(TuiMode::Tags);
+        self.previous_tag = previous_tag;
+        Ok(())
+    }
+
+    fn set_query(&mut self, query: String) -> io::Result<()> {
+        self.query = query;
+        if self.query.is_empty() {
+            self.app.set_query(None)?;
+        } else {
+            self.app.set_query(Some(&self.query))?;
+        }
+        self.go_top();
+        Ok(())
+    }
+
+    #[inline]
+    fn editor_text(&self) -> String {
+        self.textarea.lines().join("\n")
+    }
+
+    #[inline]
+    fn is_modified(&self) -> bool {
+        self.editor_text() != self.saved_text
+    }
+
+    /// Write the editor's text to its entry. Empty or unchanged text is never written, and the
+    /// first save of a new journal turns the editor into editing the entry it created. Returns
+    /// whether it can be closed, which it can't when a field is invalid, writing failed, or an
+    /// entry was emptied and whether to delete it is being asked.
+    fn on_save(&mut self) -> io::Result<bool> {
+        let journal = self.editor_text();
+        if !self.is_modified() {
+            return Ok(true)
+        }
+        if journal.trim().is_empty() {
+            if let TextMode::Edit(name) = &self.text_mode {
+                self.confirm = Some(Confirm::DeleteEmptied(name.clone()));
+                self.set_mode(TuiMode::Confirm);
+                return Ok(false)
+            }
+            return Ok(true)
+        }
+        if let Err(error) = self.app.check_fields(&journal, &self.saved_text) {
+            self.editor_error = Some(error);
+            return Ok(false)
+        }
+        let written = match &self.text_mode {
+            TextMode::Add => self.app.add_journal(&journal).map(|name| {
+                if let Some(name) = name {
+                    self.text_mode = TextMode::Edit(name);
+                }
+            }),
+            TextMode::Edit(name) => {
+                let name = name.clone();
+                self.app.edit(&name, journal.clone())
+            }
+        };
+        if let Err(error) = written {
+            self.editor_error = Some(self.app.locale().format(Message::Error, &[&error]));
+            return Ok(false)
+        }
+        if let Some(draft) = self.draft.take() {
+            self.discard_draft(&draft)?;
+        }
+        if let TextMode::Edit(name) = &self.text_mode {
+            self.start_draft(name.clone(), journal.clone());
+        }
+        self.saved_text = journal;
+        Ok(true)
+    }
+
+    /// Before the first save while editing an entry, ask for the configured fields it doesn't have
+    /// yet. Returns whether it's asking, in which case the entry is saved, and the editor closed
+    /// with `close`, once they're answered.
+    fn ask_fields(&mut self, close: bool) -> bool {
+        let text = self.editor_text();
+        if self.fields_asked || text.trim().is_empty() || !self.is_modified() {
+            return false
+        }
+        self.fields_asked = true;
+        let (front_matter, _) = split_front_matter(&text);
+        let present: Vec<&str> = fields(front_matter).map(|(key, _)| key).collect();
+        self.field_prompts = self.app.config().fields.iter().rev()
+            .filter(|field| !present.contains(&field.name.as_str()))
+            .cloned()
+            .collect();
+        if self.field_prompts.is_empty() {
+            return false
+        }
+        self.close_after_fields = close;
+        self.set_mode(TuiMode::FieldPrompt);
+        true
+    }
+
+    /// Add the typed value of the field being asked for to the entry's front-matter, and ask for
+    /// the next one. Nothing is added when nothing was typed.
+    fn on_field_input(&mut self) -> io::Result<()> {
+        let Some(field) = self.field_prompts.last().cloned() else {
+            return self.finish_fields()
+        };
+        let input = self.field_input.lines()[0].trim().to_string();
+        if !input.is_empty() {
+            if let Err(error) = field.parse(&input, self.app.locale()) {
+                self.field_input.set_style(self.error_style());
+                self.field_input.set_block(self.block(error).style(self.error_style()));
+                return Ok(())
+            }
+            self.set_editor_field(&field.name, &input);
+        }
+        self.field_prompts.pop();
+        if self.field_prompts.is_empty() {
+            self.finish_fields()
         } else {
-            self.textarea.delete_line_by_head();
-            self.textarea.delete_line_by_end();
-            self.textarea.set_style(Style::default().fg(Color::Red));
-            self.textarea.set_block(default_block("Wrong passphrase").fg(Color::Red))
+            self.set_mode(TuiMode::FieldPrompt);
+            Ok(())
         }
     }
 
-    fn on_new_journal(&mut self) -> io::Result<()> {
-        let journal = self.textarea.lines().join("\n");
-        self.app.add_journal(journal)?;
+    /// Save the entry that fields were asked for, and close the editor if that was asked for.
+    fn finish_fields(&mut self) -> io::Result<()> {
+        self.mode = TuiMode::TextEditor;
+        if self.on_save()? && self.close_after_fields {
+            self.close_editor()?;
+        }
         Ok(())
     }
 
-    fn on_edit_journal(&mut self) -> io::Result<()>{
-        let journal = self.textarea.lines().join("\n");
-        self.app.edit_nth(self.index, journal)?;
+    /// Set the front-matter field `key` of the text in the editor, keeping the cursor on the same
+    /// text.
+    fn set_editor_field(&mut self, key: &str, value: &str) {
+        let (row, column) = self.textarea.cursor();
+        let lines = self.textarea.lines().len();
+        let text = summary::set_field(&self.editor_text(), key, value);
+        self.textarea.select_all();
+        self.textarea.insert_str(&text);
+        let row = (row + self.textarea.lines().len()).saturating_sub(lines);
+        self.textarea.move_cursor(CursorMove::Jump(row as u16, column as u16));
+    }
+
+    #[inline]
+    fn start_draft(&mut self, draft: String, text: String) {
+        if self.autosaver.is_none() {
+            self.autosaver = self.app.autosaver();
+        }
+        self.draft = Some(draft);
+        self.autosaved_text = text;
+        self.autosaved_at = Instant::now();
+    }
+
+    /// Write the editor's text to its draft if it changed and `autosave_secs` have passed since
+    /// the last autosave.
+    fn autosave(&mut self) -> io::Result<()> {
+        let interval = self.app.config().autosave_secs;
+        if interval == 0 || self.autosaved_at.elapsed() < Duration::from_secs(interval) {
+            return Ok(())
+        }
+        let Some(draft) = &self.draft else {
+            return Ok(())
+        };
+        let Some(autosaver) = &self.autosaver else {
+            return Ok(())
+        };
+        let text = self.editor_text();
+        if text != self.autosaved_text {
+            autosaver.save(draft, &text);
+            self.autosaved_text = text;
+        }
+        self.autosaved_at = Instant::now();
+        match autosaver.error() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Remove the draft `draft`, after the autosaves of it still being written.
+    fn discard_draft(&self, draft: &str) -> io::Result<()> {
+        match &self.autosaver {
+            Some(autosaver) => autosaver.discard(draft),
+            None => self.app.discard_draft(draft),
+        }
+    }
+
+    fn close_editor(&mut self) -> io::Result<()> {
+        if let Some(draft) = self.draft.take() {
+            self.discard_draft(&draft)?;
+        }
+        self.textarea = TextArea::default();
+        self.saved_text = String::new();
+        self.fields_asked = false;
+        self.editor_error = None;
+        // Edited entries move to the top when sorting by modification time
+        if let TextMode::Edit(name) = &self.text_mode {
+            let name = name.clone();
+            self.select(&name);
+        }
+        self.set_mode(TuiMode::List);
+        Ok(())
+    }
+
+    /// Close the editor, asking first when there are unsaved changes.
+    fn on_discard(&mut self) -> io::Result<()> {
+        if self.is_modified() {
+            self.confirm = Some(Confirm::Discard);
+            self.set_mode(TuiMode::Confirm);
+            Ok(())
+        } else {
+            self.close_editor()
+        }
+    }
+
+    /// Write a new journal, or edit the selected one, in the configured external editor.
+    fn on_external_editor(&mut self, editor: &str) -> io::Result<()> {
+        let content = match &self.text_mode {
+            TextMode::Add => String::new(),
+            TextMode::Edit(name) => self.app.content(name)?,
+        };
+        shutdown()?;
+        let edited = edit_externally(editor, &content, self.app.locale());
+        startup()?;
+        let Some(edited) = edited? else {
+            return Ok(())
+        };
+        if edited == content {
+            return Ok(())
+        }
+        if edited.trim().is_empty() {
+            // An emptied entry is deleted only once that's confirmed
+            if let TextMode::Edit(name) = &self.text_mode {
+                let name = name.clone();
+                self.open_builtin_editor(content, &edited);
+                self.confirm = Some(Confirm::DeleteEmptied(name));
+                self.set_mode(TuiMode::Confirm);
+            }
+            return Ok(())
+        }
+        // Invalid fields are fixed in the builtin editor rather than losing the text
+        if let Err(error) = self.app.check_fields(&edited, &content) {
+            self.open_builtin_editor(content, &edited);
+            self.editor_error = Some(error);
+            return Ok(())
+        }
+        let written = match &self.text_mode {
+            TextMode::Add => self.app.add_journal(&edited).map(|_| ()),
+            TextMode::Edit(name) => {
+                let name = name.clone();
+                self.app.edit(&name, edited.clone())
+            }
+        };
+        // Neither is the text lost when it can't be written
+        if let Err(error) = written {
+            self.open_builtin_editor(content, &edited);
+            self.editor_error = Some(self.app.locale().format(Message::Error, &[&error]));
+        }
         Ok(())
     }
 
+    /// Open the builtin or the external editor for `text_mode`.
+    fn open_editor(&mut self, text_mode: TextMode) -> io::Result<Operation> {
+        self.text_mode = text_mode;
+        if let Some(editor) = self.app.config().editor.clone() {
+            // The terminal is restarted even if editing failed, as the editor may have drawn on it
+            if let Err(error) = self.on_external_editor(&editor) {
+                self.show_error(error);
+            }
+            return Ok(Operation::Restart)
+        }
+        let saved = match &self.text_mode {
+            TextMode::Add => String::new(),
+            TextMode::Edit(name) => self.app.content(name)?,
+        };
+        self.open_builtin_editor(saved.clone(), &saved);
+        Ok(Operation::Nothing)
+    }
+
+    /// Open the builtin editor on `text`, for the entry of `text_mode` as it was last `saved`.
+    fn open_builtin_editor(&mut self, saved: String, text: &str) {
+        let draft = match &self.text_mode {
+            TextMode::Add => Drafts::new_name(),
+            TextMode::Edit(name) => name.clone(),
+        };
+        self.saved_text = saved;
+        self.start_draft(draft, self.saved_text.clone());
+        self.set_mode(TuiMode::TextEditor);
+        self.textarea.insert_str(text);
+    }
+
     pub fn mask_password(&mut self) {
-        self.textarea.set_mask_char('\u{2022}')
+        self.textarea.set_mask_char(self.app.config().security.mask_char)
     } 
 
     pub fn toggle_char_mask(&mut self) {
@@ -228,93 +1633,479 @@ impl <'a>TuiApp <'a>{
         }
     }
 
+    /// Show `error` over the screen until the next key, rather than leaving the TUI for it.
+    fn show_error(&mut self, error: io::Error) {
+        self.error = Some(error.to_string());
+    }
+
     pub fn input(&mut self) -> io::Result<Operation>{
-        match crossterm::event::read()?.into() {
-            input => {
-            match self.mode {
-                TuiMode::Password => {
-                    match input {
-                        Input {
-                            key: Key::Enter,
-                            ..
-                        } => self.on_password(),
-                        Input {
-                                key: Key::Char('t'),
-                            ctrl: true,
-                            ..
-                        } => self.toggle_char_mask(),
-                        Input {
-                            key: Key::Esc,
-                            ..
-                        } => return Ok(Operation::Quit),
+        let tick = match self.mode {
+            TuiMode::TextEditor => Some(AUTOSAVE_TICK),
+            _ if self.previewer.as_ref().is_some_and(Previewer::is_pending) => Some(PREVIEW_TICK),
+            _ => None,
+        };
+        if let Some(tick) = tick {
+            if !crossterm::event::poll(tick)? {
+                match self.mode {
+                    TuiMode::TextEditor => if let Err(error) = self.autosave() {
+                        self.show_error(error);
+                    },
+                    _ => self.receive_previews(),
+                }
+                return Ok(Operation::Nothing)
+            }
+        }
+        let input: Input = crossterm::event::read()?.into();
+        // The key dismissing an error does nothing else
+        if self.error.take().is_some() {
+            return Ok(Operation::Nothing)
+        }
+        self.on_input(input).or_else(|error| {
+            self.show_error(error);
+            Ok(Operation::Nothing)
+        })
+    }
+
+    /// Act on `input` in the current mode.
+    fn on_input(&mut self, input: Input) -> io::Result<Operation> {
+        match self.mode {
+            TuiMode::Password => {
+                match input {
+                    Input {
+                        key: Key::Enter,
+                        ..
+                    } => self.on_password()?,
+                    Input {
+                            key: Key::Char('t'),
+                        ctrl: true,
+                        ..
+                    } => self.toggle_char_mask(),
+                    Input {
+                        key: Key::Esc,
+                        ..
+                    } => return Ok(Operation::Quit),
+                    input => {
+                        self.textarea.input(input);
+                    },
+                }
+            }
+            TuiMode::TextEditor => {
+                match self.app.config().keys.editor_action(&input) {
+                    Some(Action::Save) if !self.ask_fields(false) => {
+                        self.on_save()?;
+                    }
+                    Some(Action::SaveClose) if !self.ask_fields(true) => {
+                        if self.on_save()? {
+                            self.close_editor()?;
+                        }
+                    }
+                    Some(Action::Save | Action::SaveClose) => {}
+                    Some(Action::Discard) => self.on_discard()?,
+                    _ => match input {
+                        Input { key: key @ (Key::Left | Key::Right), ctrl: false, alt: false, .. } => {
+                            self.move_cursor_visually(key == Key::Right)
+                        }
                         input => {
+                            self.editor_error = None;
                             self.textarea.input(input);
-                        },
+                            self.autosave()?;
+                        }
+                    },
+                }
+            }
+            TuiMode::Filter => {
+                match input {
+                    Input {
+                        key: Key::Enter,
+                        ..
+                    } => self.on_filter(),
+                    Input {
+                        key: Key::Esc,
+                        ..
+                    } => {
+                        self.textarea = TextArea::default();
+                        self.set_mode(TuiMode::List);
                     }
+                    input => {
+                        self.textarea.input(input);
+                    },
                 }
-                TuiMode::TextEditor => {
-                    match input {
-                        Input {
-                            key: Key::Esc,
-                            ..
-                        } => {
-                            self.set_mode(TuiMode::List);
-                            match self.text_mode {
-                                TextMode::Add => self.on_new_journal()?,
-                                TextMode::Edit => self.on_edit_journal()?,
-                            }
-                            self.textarea = TextArea::default();
-                        },
-                        input => {
-                            self.textarea.input(input);
-                        },
+            }
+            TuiMode::Search => {
+                match input {
+                    Input {
+                        key: Key::Enter,
+                        ..
+                    } => {
+                        self.textarea = TextArea::default();
+                        self.set_mode(TuiMode::List);
+                    }
+                    Input {
+                        key: Key::Esc,
+                        ..
+                    } => {
+                        self.set_query(String::new())?;
+                        self.textarea = TextArea::default();
+                        self.set_mode(TuiMode::List);
                     }
+                    input => {
+                        if self.textarea.input(input) {
+                            self.set_query(self.textarea.lines()[0].clone())?;
+                        }
+                    },
                 }
-                TuiMode::List => {
-                    match input.key {
-                        Key::Char('q')=> return Ok(Operation::Quit),
-                        Key::Char('a')=> {
-                            self.text_mode = TextMode::Add;
-                            self.set_mode(TuiMode::TextEditor);
-                        },
-                        Key::Char('D')=> {
-                            self.app.delete_nth(self.index)?;
-                        },
-                        Key::Char('e')=> {
-                            self.text_mode = TextMode::Edit;
-                            self.set_mode(TuiMode::TextEditor);
-                            self.textarea.insert_str(self.app.nth_content(self.index));
-                        },
-                        Key::Char('l') | Key::Enter => {
-                            self.set_mode(TuiMode::Pager);
+            }
+            TuiMode::List => {
+                match self.app.config().keys.action(&input) {
+                    Some(Action::Quit) => return Ok(Operation::Quit),
+                    Some(Action::Add) => return self.open_editor(TextMode::Add),
+                    Some(Action::Edit) if self.index < self.entries_len() => {
+                        let name = self.selected_name();
+                        return self.open_editor(TextMode::Edit(name))
+                    }
+                    Some(Action::Delete) if self.index < self.entries_len() => {
+                        self.confirm = Some(Confirm::Delete(self.selected_name()));
+                        self.set_mode(TuiMode::Confirm);
+                    },
+                    Some(Action::Undo) => self.on_undo()?,
+                    Some(Action::History) if self.index < self.entries_len() => self.set_mode(TuiMode::History),
+                    Some(Action::Open) => self.set_mode(TuiMode::Pager),
+                    Some(Action::Filter) => self.set_mode(TuiMode::Filter),
+                    Some(Action::Search) => self.set_mode(TuiMode::Search),
+                    Some(Action::Calendar) => self.set_mode(TuiMode::Calendar),
+                    Some(Action::Tags) => self.set_mode(TuiMode::Tags),
+                    Some(Action::Stats) => self.set_mode(TuiMode::Stats),
+                    Some(Action::OnThisDay) => {
+                        let on = !self.app.on_this_day();
+                        self.app.set_on_this_day(on)?;
+                        self.go_top();
+                    }
+                    Some(Action::Random) => self.open_random(),
+                    Some(Action::ToggleSort) => self.toggle_sort(),
+                    Some(Action::ClearFilter) => {
+                        self.app.set_filter(DateFilter::default());
+                        self.app.set_tag(None);
+                        self.app.set_on_this_day(false)?;
+                        self.go_top();
+                    }
+                    Some(Action::Down) => self.increment_index(),
+                    Some(Action::Up) => self.decrement_index(),
+                    Some(Action::Top) => self.go_top(),
+                    Some(Action::Bottom) => self.go_bottom(),
+                    Some(Action::TogglePreview) => self.show_preview = !self.show_preview,
+                    Some(Action::GrowList) => self.list_width = (self.list_width + 5).min(MAX_LIST_WIDTH),
+                    Some(Action::ShrinkList) => self.list_width = self.list_width.saturating_sub(5).max(MIN_LIST_WIDTH),
+                    _ => {},
+                }
+            }
+            TuiMode::History => {
+                match (self.app.config().keys.action(&input), input.key) {
+                    (Some(Action::Down), _) if self.revision_index + 1 < self.revisions.len() => {
+                        self.revision_index += 1;
+                        self.pager_scroll = 0;
+                        self.update_diff();
+                    }
+                    (Some(Action::Up), _) if self.revision_index > 0 => {
+                        self.revision_index -= 1;
+                        self.pager_scroll = 0;
+                        self.update_diff();
+                    }
+                    _ if self.app.config().keys.matches(Action::ScrollDown, &input) => self.pager_scroll = self.pager_scroll.saturating_add(1),
+                    _ if self.app.config().keys.matches(Action::ScrollUp, &input) => self.pager_scroll = self.pager_scroll.saturating_sub(1),
+                    _ if self.app.config().keys.matches(Action::Restore, &input) => self.on_restore_revision()?,
+                    (Some(Action::Quit), _) | (_, Key::Esc) => {
+                        self.diff = vec![];
+                        self.set_mode(TuiMode::List);
+                    }
+                    _ => {}
+                }
+            }
+            TuiMode::Calendar => {
+                let day = self.calendar_day;
+                let calendar = self.app.config().calendar;
+                let keys = &self.app.config().keys;
+                let action = CALENDAR_ACTIONS.into_iter()
+                    .find(|action| keys.matches(*action, &input))
+                    .or_else(|| keys.action(&input));
+                match (action, input.key) {
+                    (Some(Action::PreviousDay), _) => self.move_calendar(day.pred_opt()),
+                    (Some(Action::NextDay), _) => self.move_calendar(day.succ_opt()),
+                    (Some(Action::Up), _) => self.move_calendar(day.checked_sub_days(Days::new(7))),
+                    (Some(Action::Down), _) => self.move_calendar(day.checked_add_days(Days::new(7))),
+                    (Some(Action::PreviousMonth), _) => self.move_calendar(calendar.add_months(day, -1)),
+                    (Some(Action::NextMonth), _) => self.move_calendar(calendar.add_months(day, 1)),
+                    (Some(Action::PreviousYear), _) => self.move_calendar(calendar.add_months(day, -12)),
+                    (Some(Action::NextYear), _) => self.move_calendar(calendar.add_months(day, 12)),
+                    (Some(Action::Today), _) => self.calendar_day = date::current().date_naive(),
+                    (Some(Action::CalendarView), _) => self.calendar_year = !self.calendar_year,
+                    (Some(Action::CalendarWords), _) => self.calendar_words = !self.calendar_words,
+                    (Some(Action::Open), _) => self.on_calendar_day(),
+                    (Some(Action::Quit), _) | (_, Key::Esc) => self.set_mode(TuiMode::List),
+                    _ => {}
+                }
+            }
+            TuiMode::Drafts => {
+                match (self.app.config().keys.action(&input), input.key) {
+                    (Some(Action::Down), _) if self.draft_index + 1 < self.drafts.len() => {
+                        self.draft_index += 1;
+                        self.pager_scroll = 0;
+                        self.update_draft_diff();
+                    }
+                    (Some(Action::Up), _) if self.draft_index > 0 => {
+                        self.draft_index -= 1;
+                        self.pager_scroll = 0;
+                        self.update_draft_diff();
+                    }
+                    _ if self.app.config().keys.matches(Action::ScrollDown, &input) => self.pager_scroll = self.pager_scroll.saturating_add(1),
+                    _ if self.app.config().keys.matches(Action::ScrollUp, &input) => self.pager_scroll = self.pager_scroll.saturating_sub(1),
+                    (Some(Action::Open), _) => self.on_recover_draft()?,
+                    _ if self.app.config().keys.matches(Action::Restore, &input) => self.on_recover_draft()?,
+                    _ if self.app.config().keys.matches(Action::DiscardDraft, &input) => {
+                        if let Some(draft) = self.drafts.get(self.draft_index).cloned() {
+                            self.confirm = Some(Confirm::DiscardDraft(draft));
+                            self.set_mode(TuiMode::Confirm);
                         }
-                        Key::Char('j')=> self.increment_index(),
-                        Key::Char('k')=> self.decrement_index(),
-                        Key::Char('g')=> self.go_top(),
-                        Key::Char('G')=> self.go_bottom(),
-                        _ =>{},
                     }
+                    (Some(Action::Quit), _) | (_, Key::Esc) => {
+                        self.diff = vec![];
+                        self.set_mode(TuiMode::List);
+                    }
+                    _ => {}
                 }
-                TuiMode::Pager => {
-                    match input.key {
-                        Key::Char('g')=> self.scroll_top(),
-                        Key::Char('G')=> self.scroll_bottom(),
-                        Key::Char('j')=> self.pager_scroll+=1,
-                        Key::Char('k')=> if self.pager_scroll > 0 {self.pager_scroll-=1},
-                        Key::Esc | Key::Char('q')=> {
-                                self.content = String::new();
-                                self.set_mode(TuiMode::List)
-                            },
-                        _ => {}
+            }
+            TuiMode::Tags => {
+                match (self.app.config().keys.action(&input), input.key) {
+                    (Some(Action::Down), _) if self.tag_index + 1 < self.tags.len() => {
+                        self.tag_index += 1;
+                        self.select_tag();
+                    }
+                    (Some(Action::Up), _) if self.tag_index > 0 => {
+                        self.tag_index -= 1;
+                        self.select_tag();
+                    }
+                    (Some(Action::Open), _) => self.set_mode(TuiMode::List),
+                    _ if !self.tags.is_empty() && self.app.config().keys.matches(Action::RenameTag, &input) => self.set_mode(TuiMode::RenameTag),
+                    (Some(Action::ClearFilter), _) => {
+                        self.app.set_tag(None);
+                        self.go_top();
+                        self.set_mode(TuiMode::List);
+                    }
+                    (Some(Action::Quit), _) | (_, Key::Esc) => {
+                        self.app.set_tag(self.previous_tag.take());
+                        self.go_top();
+                        self.set_mode(TuiMode::List);
                     }
+                    _ => {}
+                }
+            }
+            TuiMode::RenameTag => {
+                match input {
+                    Input {
+                        key: Key::Enter,
+                        ..
+                    } => self.on_rename_tag()?,
+                    Input {
+                        key: Key::Esc,
+                        ..
+                    } => {
+                        self.textarea = TextArea::default();
+                        self.mode = TuiMode::Tags;
+                    }
+                    input => {
+                        self.textarea.input(input);
+                    },
+                }
+            }
+            TuiMode::FieldPrompt => {
+                match input {
+                    Input {
+                        key: Key::Enter,
+                        ..
+                    } => self.on_field_input()?,
+                    Input {
+                        key: Key::Esc,
+                        ..
+                    } => {
+                        self.field_prompts.clear();
+                        self.finish_fields()?;
+                    }
+                    input => {
+                        self.field_input.input(input);
+                    },
+                }
+            }
+            TuiMode::Stats => {
+                match (self.app.config().keys.action(&input), input.key) {
+                    (Some(Action::Down), _) if self.series_index + 1 < self.series.fields.len() => self.series_index += 1,
+                    (Some(Action::Up), _) if self.series_index > 0 => self.series_index -= 1,
+                    (Some(Action::Quit), _) | (_, Key::Esc) => {
+                        self.writing = Writing::default();
+                        self.series = Series::default();
+                        self.set_mode(TuiMode::List);
+                    }
+                    _ => {}
+                }
+            }
+            TuiMode::Confirm => {
+                let keys = &self.app.config().keys;
+                if keys.matches(Action::Yes, &input) {
+                    self.on_confirm(false)?;
+                } else if keys.matches(Action::Shred, &input) && self.confirm.as_ref().is_some_and(Confirm::can_shred) {
+                    self.on_confirm(true)?;
+                } else if keys.matches(Action::No, &input) {
+                    let mode = match self.confirm.take() {
+                        Some(Confirm::Discard) => TuiMode::TextEditor,
+                        Some(Confirm::DiscardDraft(_)) => TuiMode::Drafts,
+                        Some(Confirm::DeleteEmptied(_)) => return self.close_editor().map(|_| Operation::Nothing),
+                        _ => TuiMode::List,
+                    };
+                    self.mode = mode;
+                }
+            }
+            TuiMode::Pager => {
+                match (self.app.config().keys.action(&input), input.key) {
+                    (Some(Action::Top), _) => self.scroll_top(),
+                    (Some(Action::Bottom), _) => self.scroll_bottom(),
+                    (Some(Action::Down), _) => self.pager_scroll = self.pager_scroll.saturating_add(1),
+                    (Some(Action::Up), _) => self.pager_scroll = self.pager_scroll.saturating_sub(1),
+                    (Some(Action::HalfPageDown), _) => self.pager_scroll = self.pager_scroll.saturating_add(self.pager_height / 2),
+                    (Some(Action::HalfPageUp), _) => self.pager_scroll = self.pager_scroll.saturating_sub(self.pager_height / 2),
+                    (Some(Action::PageDown), _) => self.pager_scroll = self.pager_scroll.saturating_add(self.pager_height),
+                    (Some(Action::PageUp), _) => self.pager_scroll = self.pager_scroll.saturating_sub(self.pager_height),
+                    (Some(Action::NextEntry), _) => self.pager_entry(1),
+                    (Some(Action::Random), _) => self.open_random(),
+                    (Some(Action::PreviousEntry), _) => self.pager_entry(-1),
+                    (Some(Action::Search), _) => {
+                        self.search_backward = false;
+                        self.set_mode(TuiMode::PagerSearch);
+                    }
+                    (Some(Action::SearchBackward), _) => {
+                        self.search_backward = true;
+                        self.set_mode(TuiMode::PagerSearch);
+                    }
+                    (Some(Action::NextMatch), _) => self.next_match(self.search_backward),
+                    (Some(Action::PreviousMatch), _) => self.next_match(!self.search_backward),
+                    (Some(Action::Quit), _) | (_, Key::Esc) => {
+                            self.content = String::new();
+                            self.pager_lines = vec![];
+                            self.pager_search = None;
+                            self.pager_matches = vec![];
+                            self.set_mode(TuiMode::List)
+                        },
+                    _ => {}
+                }
+            }
+            TuiMode::PagerSearch => {
+                match input {
+                    Input {
+                        key: Key::Enter,
+                        ..
+                    } => {
+                        let pattern = self.textarea.lines()[0].clone();
+                        self.textarea = TextArea::default();
+                        self.mode = TuiMode::Pager;
+                        self.on_pager_search(&pattern);
+                    }
+                    Input {
+                        key: Key::Esc,
+                        ..
+                    } => {
+                        self.textarea = TextArea::default();
+                        self.mode = TuiMode::Pager;
+                    }
+                    input => {
+                        self.textarea.input(input);
+                    },
                 }
             }
-        }
         }
         Ok(Operation::Nothing)
     }
 }
 
+/// Line diff from `old` to `new`, with removed lines in red and added lines in green.
+fn diff_lines(old: &str, new: &str) -> Vec<Line<'static>> {
+    TextDiff::from_lines(old, new).iter_all_changes()
+        .map(|change| {
+            let (sign, style) = match change.tag() {
+                ChangeTag::Delete => ("-", Style::default().fg(Color::Red)),
+                ChangeTag::Insert => ("+", Style::default().fg(Color::Green)),
+                ChangeTag::Equal => (" ", Style::default()),
+            };
+            let text = change.value().trim_end_matches('\n');
+            Line::styled(format!("{}{}", sign, text), style)
+        })
+        .collect()
+}
+
+/// `line` with the text in the byte `range` restyled with `style`.
+fn highlight(line: &Line<'static>, range: &Range<usize>, style: Style) -> Line<'static> {
+    let mut spans = vec![];
+    let mut start = 0;
+    for span in &line.spans {
+        let end = start + span.content.len();
+        let from = range.start.clamp(start, end) - start;
+        let to = range.end.clamp(start, end) - start;
+        let content = span.content.as_ref();
+        for (text, span_style) in [
+            (&content[..from], span.style),
+            (&content[from..to], span.style.patch(style)),
+            (&content[to..], span.style),
+        ] {
+            if !text.is_empty() {
+                spans.push(Span::styled(text.to_string(), span_style));
+            }
+        }
+        start = end;
+    }
+    Line::from(spans)
+}
+
+/// Let `editor` edit `content` in a file of a private temporary directory, which is shredded
+/// afterwards. `None` when the editor fails or is aborted.
+fn edit_externally(editor: &str, content: &str, locale: Locale) -> io::Result<Option<String>> {
+    let dir = env::temp_dir().join(format!("jou-{}-{:016x}", process::id(), rand::random::<u64>()));
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&dir)?;
+    let path = dir.join("entry.md");
+    let edited = write_and_edit(editor, &path, content, locale);
+    if path.exists() {
+        file::shred(&path)?;
+    }
+    fs::remove_dir_all(&dir)?;
+    edited
+}
+
+fn write_and_edit(editor: &str, path: &Path, content: &str, locale: Locale) -> io::Result<Option<String>> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(content.as_bytes())?;
+
+    let command = shell_words::split(editor).map_err(|error| io::Error::new(
+        io::ErrorKind::InvalidInput,
+        locale.format(Message::InvalidEditor, &[&editor, &error]),
+    ))?;
+    let status = match command.split_first() {
+        Some((program, args)) => process::Command::new(program).args(args).arg(path).status()?,
+        None => return Err(io::Error::new(io::ErrorKind::InvalidInput, locale.text(Message::EmptyEditor))),
+    };
+    if !status.success() {
+        return Ok(None)
+    }
+    fs::read_to_string(path).map(Some)
+}
+
+/// A centered bordered rectangle `percent_x` wide and as high as `text` wrapped in it needs.
+fn text_rect(r: Rect, percent_x: u16, text: &str) -> Rect {
+    let width = (r.width * percent_x / 100).saturating_sub(2).max(1);
+    let lines = (text.width() as u16).div_ceil(width).max(1);
+    centered_rect(r, percent_x, lines + 2)
+}
+
 fn centered_rect(r: Rect, percent_x: u16, size_y: u16) -> Rect {
   let popup_layout = Layout::default()
     .direction(Direction::Vertical)
@@ -335,9 +2126,16 @@ fn centered_rect(r: Rect, percent_x: u16, size_y: u16) -> Rect {
     .split(popup_layout[1])[1]
 }
 
-#[inline]
+/// Run the TUI, restoring the terminal however it ends.
 pub fn run(app: &mut App) -> io::Result<()>{
     startup()?;
+    let result = run_loop(app);
+    let restored = shutdown();
+    result.and(restored)
+}
+
+#[inline]
+fn run_loop(app: &mut App) -> io::Result<()>{
     let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
     let mut app = TuiApp::new(app);
     let mut list_state = ListState::default();
@@ -354,6 +2152,5 @@ pub fn run(app: &mut App) -> io::Result<()>{
             Operation::Nothing => {},
         }
     }
-    shutdown()?;
     Ok(())
 }
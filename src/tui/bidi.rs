@@ -0,0 +1,180 @@
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use unicode_bidi::ParagraphBidiInfo;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Brackets and the like, drawn mirrored inside right-to-left text
+const MIRRORED: [(&str, &str); 8] = [
+    ("(", ")"), (")", "("), ("[", "]"), ("]", "["), ("{", "}"), ("}", "{"), ("«", "»"), ("»", "«"),
+];
+
+/// Whether `text` has right-to-left characters, like Persian or Arabic.
+#[inline]
+fn has_rtl(text: &str) -> bool {
+    text.chars().any(|c| matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF))
+}
+
+/// How a line of grapheme clusters is shown by the Unicode bidi algorithm.
+pub struct Visual {
+    /// Indices of the clusters in the order they're shown in, left to right
+    pub order: Vec<usize>,
+    /// Whether each cluster is inside right-to-left text
+    pub rtl: Vec<bool>,
+    /// Whether the line is right-to-left, going by its first strong character
+    pub rtl_line: bool,
+}
+
+impl Visual {
+    pub fn new(clusters: &[&str]) -> Self {
+        let text = clusters.concat();
+        if !has_rtl(&text) {
+            return Visual {
+                order: (0..clusters.len()).collect(),
+                rtl: vec![false; clusters.len()],
+                rtl_line: false,
+            }
+        }
+        let mut starts = Vec::with_capacity(clusters.len());
+        let mut offset = 0;
+        for cluster in clusters {
+            starts.push(offset);
+            offset += cluster.len();
+        }
+        let info = ParagraphBidiInfo::new(&text, None);
+        let (levels, runs) = info.visual_runs(0..text.len());
+        let mut order = Vec::with_capacity(clusters.len());
+        for run in runs {
+            let first = starts.partition_point(|start| *start < run.start);
+            let last = starts.partition_point(|start| *start < run.end);
+            if levels[run.start].is_rtl() {
+                order.extend((first..last).rev());
+            } else {
+                order.extend(first..last);
+            }
+        }
+        Visual {
+            order,
+            rtl: starts.iter().map(|start| levels[*start].is_rtl()).collect(),
+            rtl_line: info.paragraph_level.is_rtl(),
+        }
+    }
+}
+
+/// Redraw the lines in `area` of `buffer` that have right-to-left text in visual order, aligned to
+/// the right when they start right-to-left. Widgets draw text in logical order, which scrambles
+/// Persian and Arabic.
+pub fn reorder(buffer: &mut Buffer, area: Rect) {
+    let area = area.intersection(buffer.area);
+    for y in area.top()..area.bottom() {
+        // One cell per grapheme; the cells covered by wide ones are skipped
+        let mut cells: Vec<Cell> = vec![];
+        let mut x = area.left();
+        while x < area.right() {
+            let cell = buffer.get(x, y).clone();
+            x += cell.symbol().width().max(1) as u16;
+            cells.push(cell);
+        }
+        let symbols: Vec<&str> = cells.iter().map(Cell::symbol).collect();
+        if !has_rtl(&symbols.concat()) {
+            continue
+        }
+        // Padding after the text isn't part of it
+        let mut padding = cells.last().cloned().unwrap_or_default();
+        padding.set_symbol(" ");
+        let len = cells.iter().rposition(|cell| *cell != padding).map_or(0, |last| last + 1);
+        let visual = Visual::new(&symbols[..len]);
+        let width: u16 = symbols[..len].iter().map(|symbol| symbol.width().max(1) as u16).sum();
+        let mut x = if visual.rtl_line { area.right().saturating_sub(width).max(area.left()) } else { area.left() };
+        let reordered: Vec<(Cell, bool)> = visual.order.iter().map(|&i| (cells[i].clone(), visual.rtl[i])).collect();
+        for column in area.left()..area.right() {
+            *buffer.get_mut(column, y) = padding.clone();
+        }
+        for (mut cell, mirrored) in reordered {
+            if mirrored {
+                if let Some((_, mirror)) = MIRRORED.iter().find(|(symbol, _)| *symbol == cell.symbol()) {
+                    cell.set_symbol(mirror);
+                }
+            }
+            let width = cell.symbol().width().max(1) as u16;
+            if x + width > area.right() {
+                break
+            }
+            *buffer.get_mut(x, y) = cell;
+            x += width;
+        }
+    }
+}
+
+/// `line` in visual order, for text drawn where `reorder` can't reach it, like block titles.
+pub fn visual_line(line: Line) -> Line {
+    if !line.spans.iter().any(|span| has_rtl(&span.content)) {
+        return line
+    }
+    let styled: Vec<(&str, _)> = line.spans.iter()
+        .flat_map(|span| span.content.graphemes(true).map(|cluster| (cluster, span.style)))
+        .collect();
+    let clusters: Vec<&str> = styled.iter().map(|(cluster, _)| *cluster).collect();
+    let visual = Visual::new(&clusters);
+    let spans: Vec<Span> = visual.order.iter()
+        .map(|&i| {
+            let (cluster, style) = styled[i];
+            let mirror = MIRRORED.iter().find(|(symbol, _)| visual.rtl[i] && *symbol == cluster);
+            Span::styled(mirror.map_or(cluster, |(_, mirror)| *mirror).to_string(), style)
+        })
+        .collect();
+    Line::from(spans).style(line.style)
+}
+
+/// The grapheme clusters of `line`, which the cursor moves over as a whole.
+#[inline]
+pub fn clusters(line: &str) -> Vec<&str> {
+    line.graphemes(true).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn of(text: &str) -> Visual {
+        let clusters: Vec<&str> = text.graphemes(true).collect();
+        Visual::new(&clusters)
+    }
+
+    #[test]
+    fn left_to_right_text_stays() {
+        let visual = of("a (b) c");
+        assert_eq!(visual.order, (0..7).collect::<Vec<_>>());
+        assert!(visual.rtl.iter().all(|rtl| !rtl));
+        assert!(!visual.rtl_line);
+    }
+
+    #[test]
+    fn right_to_left_text_is_reversed() {
+        let visual = of("سلام");
+        assert_eq!(visual.order, [3, 2, 1, 0]);
+        assert_eq!(visual.rtl, [true; 4]);
+        assert!(visual.rtl_line);
+    }
+
+    #[test]
+    fn mixed_text_reverses_runs() {
+        let visual = of("ab سلام cd");
+        assert_eq!(visual.order, [0, 1, 2, 6, 5, 4, 3, 7, 8, 9]);
+        assert!(!visual.rtl_line);
+        assert_eq!(visual.rtl.iter().filter(|rtl| **rtl).count(), 4);
+
+        let visual = of("سلام ab");
+        assert!(visual.rtl_line);
+        assert_eq!(visual.order, [5, 6, 4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn reorder_mirrors_and_aligns() {
+        let mut buffer = Buffer::with_lines(vec!["(سلام)    ", "plain (x) "]);
+        let area = buffer.area;
+        reorder(&mut buffer, area);
+        assert_eq!(buffer, Buffer::with_lines(vec!["    (مالس)", "plain (x) "]));
+    }
+}
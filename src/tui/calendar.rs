@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use chrono::{Datelike, NaiveDate};
+use ratatui::prelude::*;
+
+use crate::app::file::date::{jalali, Calendar, DateStyle};
+use crate::locale::Locale;
+
+/// Width of a month grid: seven days of three columns each
+pub const MONTH_WIDTH: usize = 21;
+
+/// Backgrounds of days with no activity up to the most, like a contribution graph
+const SHADES: [Color; 5] = [
+    Color::Reset,
+    Color::Indexed(22),
+    Color::Indexed(28),
+    Color::Indexed(34),
+    Color::Indexed(40),
+];
+
+/// Entries and words written each day, shaded relative to the busiest day.
+pub struct Heatmap {
+    pub activity: HashMap<NaiveDate, (usize, usize)>,
+    /// Shade by words written instead of by entries
+    pub by_words: bool,
+    max: usize,
+    style: DateStyle,
+    locale: Locale,
+}
+
+impl Heatmap {
+    pub fn new(activity: HashMap<NaiveDate, (usize, usize)>, by_words: bool, style: DateStyle, locale: Locale) -> Self {
+        let max = activity.values()
+            .map(|&(entries, words)| if by_words { words } else { entries })
+            .max()
+            .unwrap_or(0);
+        Heatmap { activity, by_words, max, style, locale }
+    }
+
+    #[inline]
+    pub fn day(&self, date: NaiveDate) -> (usize, usize) {
+        self.activity.get(&date).copied().unwrap_or_default()
+    }
+
+    fn shade(&self, date: NaiveDate) -> Color {
+        let (entries, words) = self.day(date);
+        let value = if self.by_words { words } else { entries };
+        if value == 0 || self.max == 0 {
+            // Days with entries not decrypted yet still show up when shading by words
+            return if entries > 0 { SHADES[1] } else { SHADES[0] }
+        }
+        SHADES[(value * 4).div_ceil(self.max).clamp(1, 4)]
+    }
+
+    /// The month of `date` as a grid of weeks starting on the calendar's first weekday,
+    /// `MONTH_WIDTH` columns wide, with `selected` reversed.
+    pub fn month(&self, date: NaiveDate, selected: NaiveDate) -> Vec<Line<'static>> {
+        let calendar = self.style.calendar;
+        let first = calendar.first_of_month(date);
+        let (year, month, _) = calendar.ymd(first);
+        let title = self.style.digits(&format!("{} {}", calendar.month_name(month, self.locale), year));
+        let initials = match calendar {
+            Calendar::Gregorian => self.locale.weekday_initials(),
+            Calendar::Jalali => jalali::WEEKDAY_INITIALS,
+        };
+        let week_start = calendar.week_start().num_days_from_monday() as usize;
+        let header: String = (0..7)
+            .map(|i| format!("{:>2} ", initials[(week_start + i) % 7]))
+            .collect();
+        let mut lines = vec![
+            Line::from(Span::styled(format!("{:^width$}", title, width = MONTH_WIDTH), Style::new().add_modifier(Modifier::BOLD))),
+            Line::from(Span::styled(header, Style::new().add_modifier(Modifier::DIM))),
+        ];
+        let next = calendar.add_months(first, 1).unwrap();
+        let weekday = |day: NaiveDate| (day.weekday().num_days_from_monday() as usize + 7 - week_start) % 7;
+        let mut week = vec![Span::raw("   ".repeat(weekday(first)))];
+        for day in first.iter_days().take_while(|day| *day < next) {
+            let mut style = Style::new().bg(self.shade(day));
+            if day == selected {
+                style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            week.push(Span::styled(self.style.digits(&format!("{:>2}", calendar.ymd(day).2)), style));
+            week.push(Span::raw(" "));
+            if weekday(day) == 6 {
+                lines.push(Line::from(std::mem::take(&mut week)));
+            }
+        }
+        if !week.is_empty() {
+            lines.push(Line::from(week));
+        }
+        // Every month takes as many lines, so they line up side by side
+        while lines.len() < 8 {
+            lines.push(Line::raw(""));
+        }
+        lines
+    }
+
+    /// All twelve months of the year of `selected`, `columns` months across.
+    pub fn year(&self, selected: NaiveDate, columns: usize) -> Vec<Line<'static>> {
+        let columns = columns.clamp(1, 12);
+        let calendar = self.style.calendar;
+        let (year, _, _) = calendar.ymd(selected);
+        let months: Vec<Vec<Line>> = (1..=12)
+            .map(|month| self.month(calendar.date(year, month, 1).unwrap(), selected))
+            .collect();
+        let mut lines = vec![];
+        for row in months.chunks(columns) {
+            for i in 0..row[0].len() {
+                let mut spans = vec![];
+                for month in row {
+                    let line = &month[i];
+                    spans.extend(line.spans.iter().cloned());
+                    spans.push(Span::raw(" ".repeat(MONTH_WIDTH + 2 - line.width())));
+                }
+                lines.push(Line::from(spans));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(lines: &[Line]) -> Vec<String> {
+        lines.iter()
+            .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn gregorian_month() {
+        let activity = HashMap::from([(day(2024, 2, 1), (2, 10)), (day(2024, 2, 2), (1, 300))]);
+        let style = DateStyle { calendar: Calendar::Gregorian, persian_digits: false };
+        let heatmap = Heatmap::new(activity, false, style, Locale::En);
+        let lines = heatmap.month(day(2024, 2, 10), day(2024, 2, 29));
+        assert_eq!(plain(&lines), [
+            "    February 2024",
+            "Mo Tu We Th Fr Sa Su",
+            "          1  2  3  4",
+            " 5  6  7  8  9 10 11",
+            "12 13 14 15 16 17 18",
+            "19 20 21 22 23 24 25",
+            "26 27 28 29",
+            "",
+        ]);
+        // Days are shaded relative to the busiest, and the selected one is reversed
+        assert_eq!(lines[2].spans[1].style.bg, Some(SHADES[4]));
+        assert_eq!(lines[2].spans[3].style.bg, Some(SHADES[2]));
+        assert_eq!(lines[2].spans[5].style.bg, Some(SHADES[0]));
+        assert!(lines[6].spans[6].style.add_modifier.contains(Modifier::REVERSED));
+
+        let by_words = Heatmap::new(heatmap.activity, true, style, Locale::En);
+        let lines = by_words.month(day(2024, 2, 1), day(2024, 2, 1));
+        assert_eq!(lines[2].spans[1].style.bg, Some(SHADES[1]));
+        assert_eq!(lines[2].spans[3].style.bg, Some(SHADES[4]));
+        assert_eq!(by_words.day(day(2024, 2, 1)), (2, 10));
+        assert_eq!(by_words.day(day(2024, 2, 3)), (0, 0));
+    }
+
+    #[test]
+    fn jalali_month() {
+        // Esfand 1402 starts on Tuesday 2024-02-20, and weeks start on Saturday
+        let style = DateStyle { calendar: Calendar::Jalali, persian_digits: true };
+        let heatmap = Heatmap::new(HashMap::new(), false, style, Locale::Fa);
+        assert_eq!(plain(&heatmap.month(day(2024, 3, 1), day(2024, 3, 1))), [
+            "     اسفند ۱۴۰۲",
+            " ش  ی  د  س  چ  پ  ج",
+            "          ۱  ۲  ۳  ۴",
+            " ۵  ۶  ۷  ۸  ۹ ۱۰ ۱۱",
+            "۱۲ ۱۳ ۱۴ ۱۵ ۱۶ ۱۷ ۱۸",
+            "۱۹ ۲۰ ۲۱ ۲۲ ۲۳ ۲۴ ۲۵",
+            "۲۶ ۲۷ ۲۸ ۲۹",
+            "",
+        ]);
+    }
+}
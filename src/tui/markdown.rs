@@ -0,0 +1,412 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::summary::split_front_matter;
+
+/// Render the Markdown `text` as styled lines, soft wrapped to `width` columns. A front-matter
+/// block is shown dimmed, as it is.
+pub fn render(text: &str, width: u16) -> Vec<Line<'static>> {
+    let mut renderer = Renderer::new(width.max(1) as usize);
+    let (front_matter, body) = split_front_matter(text);
+    if !front_matter.is_empty() {
+        let dim = Style::new().add_modifier(Modifier::DIM);
+        for line in front_matter.lines() {
+            renderer.push(line, dim);
+            renderer.flush();
+        }
+        renderer.blank();
+    }
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS | Options::ENABLE_TABLES;
+    for event in Parser::new_ext(body, options) {
+        renderer.event(event);
+    }
+    renderer.flush();
+    if renderer.separated {
+        renderer.lines.pop();
+    }
+    renderer.lines
+}
+
+struct Renderer {
+    lines: Vec<Line<'static>>,
+    width: usize,
+    /// Inline styles, innermost last
+    styles: Vec<Style>,
+    /// Spans of the block being written, not wrapped yet
+    spans: Vec<Span<'static>>,
+    /// Written before each line of the block, like `│ ` in block quotes
+    prefixes: Vec<Span<'static>>,
+    /// Written before the first line of a list item instead of its indentation
+    bullet: Option<Span<'static>>,
+    /// Next number of each open list, `None` for bulleted lists
+    lists: Vec<Option<u64>>,
+    code_block: bool,
+    links: Vec<String>,
+    /// Whether the last line is the empty line after a block
+    separated: bool,
+}
+
+impl Renderer {
+    fn new(width: usize) -> Self {
+        Renderer {
+            lines: vec![],
+            width,
+            styles: vec![Style::new()],
+            spans: vec![],
+            prefixes: vec![],
+            bullet: None,
+            lists: vec![],
+            code_block: false,
+            links: vec![],
+            separated: false,
+        }
+    }
+
+    #[inline]
+    fn style(&self) -> Style {
+        *self.styles.last().unwrap()
+    }
+
+    fn push_style(&mut self, style: Style) {
+        self.styles.push(self.style().patch(style));
+    }
+
+    fn push<S: Into<String>>(&mut self, text: S, style: Style) {
+        self.spans.push(Span::styled(text.into(), style));
+    }
+
+    /// End the current line, wrapping what's been written of it.
+    fn flush(&mut self) {
+        if self.spans.is_empty() && self.bullet.is_none() {
+            return
+        }
+        let spans = std::mem::take(&mut self.spans);
+        let indent: usize = self.prefixes.iter().map(Span::width).sum();
+        let mut first = self.bullet.take();
+        for content in wrap(spans, self.width.saturating_sub(indent).max(1)) {
+            let mut line = self.prefixes.clone();
+            // The bullet replaces the indentation the item pushed for it
+            if let Some(bullet) = first.take() {
+                line.pop();
+                line.push(bullet);
+            }
+            line.extend(content);
+            self.lines.push(Line::from(line));
+        }
+        self.separated = false;
+    }
+
+    /// Separate blocks with an empty line.
+    fn blank(&mut self) {
+        self.flush();
+        if !self.separated && !self.lines.is_empty() {
+            let prefixes: Vec<Span> = self.prefixes.iter()
+                .map(|prefix| Span::styled(prefix.content.trim_end().to_string(), prefix.style))
+                .collect();
+            self.lines.push(Line::from(prefixes));
+            self.separated = true;
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code_block => {
+                for line in text.lines() {
+                    self.push(line, self.style());
+                    self.flush();
+                }
+            }
+            Event::Text(text) => self.push(text.to_string(), self.style()),
+            Event::Code(code) => self.push(code.to_string(), self.style().fg(Color::Yellow)),
+            Event::Html(html) | Event::InlineHtml(html) => self.push(html.to_string(), self.style()),
+            Event::FootnoteReference(label) => self.push(format!("[{}]", label), self.style()),
+            Event::SoftBreak => self.push(" ", self.style()),
+            Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.flush();
+                let indent: usize = self.prefixes.iter().map(Span::width).sum();
+                self.push("─".repeat(self.width.saturating_sub(indent)), Style::new().add_modifier(Modifier::DIM));
+                self.blank();
+            }
+            Event::TaskListMarker(checked) => self.push(if checked { "[x] " } else { "[ ] " }, self.style()),
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.flush();
+                let style = match level {
+                    HeadingLevel::H1 => Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                    HeadingLevel::H2 => Style::new().add_modifier(Modifier::BOLD),
+                    _ => Style::new().add_modifier(Modifier::BOLD | Modifier::ITALIC),
+                };
+                self.push_style(style);
+            }
+            Tag::BlockQuote => {
+                self.flush();
+                self.prefixes.push(Span::styled("│ ", Style::new().add_modifier(Modifier::DIM)));
+                self.push_style(Style::new().add_modifier(Modifier::ITALIC));
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush();
+                if let CodeBlockKind::Fenced(language) = kind {
+                    if !language.is_empty() {
+                        self.push(language.to_string(), Style::new().add_modifier(Modifier::DIM));
+                        self.flush();
+                    }
+                }
+                self.code_block = true;
+                self.prefixes.push(Span::raw("    "));
+                self.push_style(Style::new().fg(Color::Yellow));
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => String::from("• "),
+                };
+                self.prefixes.push(Span::raw(" ".repeat(bullet.width())));
+                self.bullet = Some(Span::raw(bullet));
+            }
+            Tag::Emphasis => self.push_style(Style::new().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.push_style(Style::new().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self.push_style(Style::new().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                self.links.push(dest_url.to_string());
+                self.push_style(Style::new().fg(Color::Blue).add_modifier(Modifier::UNDERLINED));
+            }
+            Tag::TableCell if !self.spans.is_empty() => self.push(" │ ", Style::new().add_modifier(Modifier::DIM)),
+            Tag::TableHead => self.push_style(Style::new().add_modifier(Modifier::BOLD)),
+            Tag::Paragraph | Tag::HtmlBlock | Tag::FootnoteDefinition(_) | Tag::Table(_)
+                | Tag::TableRow | Tag::TableCell | Tag::MetadataBlock(_) => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::HtmlBlock | TagEnd::Table => {
+                if self.lists.is_empty() {
+                    self.blank()
+                } else {
+                    self.flush()
+                }
+            }
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                self.blank();
+            }
+            TagEnd::BlockQuote => {
+                self.flush();
+                // The quote's last block was separated inside the quote
+                if self.separated {
+                    self.lines.pop();
+                    self.separated = false;
+                }
+                self.styles.pop();
+                self.prefixes.pop();
+                self.blank();
+            }
+            TagEnd::CodeBlock => {
+                self.code_block = false;
+                self.styles.pop();
+                self.prefixes.pop();
+                self.blank();
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank();
+                }
+            }
+            TagEnd::Item => {
+                self.flush();
+                self.prefixes.pop();
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
+            }
+            TagEnd::Link | TagEnd::Image => {
+                self.styles.pop();
+                if let Some(url) = self.links.pop() {
+                    let text: String = self.spans.iter().map(|span| span.content.as_ref()).collect();
+                    if !url.is_empty() && !text.ends_with(&url) {
+                        self.push(format!(" ({})", url), Style::new().add_modifier(Modifier::DIM));
+                    }
+                }
+            }
+            TagEnd::TableHead => {
+                self.styles.pop();
+                self.flush();
+            }
+            TagEnd::TableRow => self.flush(),
+            TagEnd::TableCell | TagEnd::FootnoteDefinition | TagEnd::MetadataBlock(_) => {}
+        }
+    }
+}
+
+/// Break `spans` into lines of at most `width` columns, at whitespace where possible.
+fn wrap(spans: Vec<Span<'static>>, width: usize) -> Vec<Vec<Span<'static>>> {
+    let mut lines = vec![];
+    let mut line: Vec<Span<'static>> = vec![];
+    let mut line_width = 0;
+    for span in spans {
+        for piece in pieces(&span.content) {
+            let piece_width = piece.width();
+            let is_space = piece.starts_with(char::is_whitespace);
+            if line_width + piece_width > width {
+                if is_space {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                    continue
+                }
+                if line_width > 0 {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+            }
+            if is_space && line_width == 0 && !lines.is_empty() {
+                continue
+            }
+            // Words longer than a whole line are broken anywhere
+            let mut rest = piece;
+            while rest.width() > width - line_width {
+                let mut split = split_at_width(rest, width - line_width);
+                if split == 0 {
+                    if line_width > 0 {
+                        lines.push(std::mem::take(&mut line));
+                        line_width = 0;
+                        continue
+                    }
+                    split = rest.graphemes(true).next().map_or(rest.len(), str::len);
+                }
+                line.push(Span::styled(rest[..split].to_string(), span.style));
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+                rest = &rest[split..];
+            }
+            if !rest.is_empty() {
+                line_width += rest.width();
+                line.push(Span::styled(rest.to_string(), span.style));
+            }
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// `text` split into runs of whitespace and runs of anything else.
+fn pieces(text: &str) -> Vec<&str> {
+    let mut pieces = vec![];
+    let mut start = 0;
+    let mut space = None;
+    for (i, c) in text.char_indices() {
+        let is_space = c.is_whitespace();
+        if space.is_some_and(|space| space != is_space) {
+            pieces.push(&text[start..i]);
+            start = i;
+        }
+        space = Some(is_space);
+    }
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+    pieces
+}
+
+/// Byte index of the longest prefix of `text` at most `width` columns wide. Combining characters
+/// stay with the character they're on.
+fn split_at_width(text: &str, width: usize) -> usize {
+    let mut used = 0;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > width {
+            return i
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &str, width: u16) -> Vec<String> {
+        render(text, width).iter()
+            .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    fn wrapped(text: &str, width: usize) -> Vec<String> {
+        wrap(vec![Span::raw(text.to_string())], width).iter()
+            .map(|line| line.iter().map(|span| span.content.as_ref()).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(plain("- one\n- two\n\n1. first\n2. second\n\nafter", 20),
+            ["• one", "• two", "", "1. first", "2. second", "", "after"]);
+        // Wrapped and nested items are indented under their bullet
+        assert_eq!(plain("- a long item that wraps\n  - nested", 10),
+            ["• a long", "  item", "  that", "  wraps", "  • nested"]);
+        assert_eq!(plain("3. third\n4. fourth", 20), ["3. third", "4. fourth"]);
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(plain("> quoted text here\n>\n> second\n\nafter", 10),
+            ["│ quoted", "│ text", "│ here", "│", "│ second", "", "after"]);
+    }
+
+    #[test]
+    fn code_blocks() {
+        // Code isn't parsed as Markdown, and keeps its empty lines
+        assert_eq!(plain("```rust\nfn main() {}\n\n*not emphasis*\n```\ntext", 40),
+            ["rust", "    fn main() {}", "", "    *not emphasis*", "", "text"]);
+        assert_eq!(plain("    indented code", 40), ["    indented code"]);
+    }
+
+    #[test]
+    fn long_words() {
+        assert_eq!(wrapped("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        // A word that doesn't fit after others starts a line of its own
+        assert_eq!(wrapped("ab abcdefghij", 4), ["ab", "abcd", "efgh", "ij"]);
+        assert_eq!(plain("see https://example.com/a/long/path", 12),
+            ["see", "https://exam", "ple.com/a/lo", "ng/path"]);
+    }
+
+    #[test]
+    fn wide_characters() {
+        assert_eq!(wrapped("日本語テキスト", 5), ["日本", "語テ", "キス", "ト"]);
+        assert_eq!(wrapped("日本 語", 3), ["日", "本", "語"]);
+        // A wide character never goes past the width, even when nothing else fits
+        assert_eq!(wrapped("日本", 1), ["日", "本"]);
+    }
+
+    #[test]
+    fn pieces_and_widths() {
+        assert_eq!(pieces("a  b c"), ["a", "  ", "b", " ", "c"]);
+        assert_eq!(pieces(" lead"), [" ", "lead"]);
+        assert!(pieces("").is_empty());
+        assert_eq!(split_at_width("日本語", 3), "日".len());
+        assert_eq!(split_at_width("日本語", 4), "日本".len());
+        assert_eq!(split_at_width("abc", 5), 3);
+        // Combining characters stay with their base
+        assert_eq!(split_at_width("e\u{301}x", 1), "e\u{301}".len());
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, FixedOffset, Local};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::app::file::date::DateStyle;
+use crate::config::Field;
+
+/// Height of a sparkline bar at the highest value
+pub const BAR_MAX: u64 = 100;
+const DAY: f64 = 24.0 * 60.0 * 60.0;
+
+/// Values of a field over time, as a `Chart` and a `Sparkline` show them.
+pub struct Trend {
+    /// Seconds since the epoch and value of each entry, oldest first
+    pub points: Vec<(f64, f64)>,
+    /// Lowest and highest value shown: the field's range, widened to the values outside it
+    pub bounds: [f64; 2],
+}
+
+impl Trend {
+    pub fn new(values: &[(DateTime<FixedOffset>, f64)], field: Option<&Field>) -> Self {
+        let points: Vec<(f64, f64)> = values.iter()
+            .map(|(created, value)| (created.timestamp() as f64, *value))
+            .collect();
+        let low = points.iter().map(|(_, value)| *value).fold(f64::INFINITY, f64::min);
+        let high = points.iter().map(|(_, value)| *value).fold(f64::NEG_INFINITY, f64::max);
+        let low = field.and_then(|field| field.min).map_or(low, |min| min.min(low));
+        let high = field.and_then(|field| field.max).map_or(high, |max| max.max(high));
+        let bounds = if low < high { [low, high] } else { [low - 1.0, high + 1.0] };
+        Trend { points, bounds }
+    }
+
+    /// A line chart of the values, with the dates they were written along the bottom.
+    pub fn chart(&self, style: DateStyle) -> Chart<'_> {
+        let first = self.points.first().map_or(0.0, |(time, _)| *time);
+        let last = self.points.last().map_or(0.0, |(time, _)| *time);
+        let x = if first < last { [first, last] } else { [first - DAY, last + DAY] };
+        let date = |time: f64| {
+            let time = DateTime::from_timestamp(time as i64, 0).unwrap_or_default().with_timezone(&Local);
+            Span::raw(style.format(&time, "%-d %b %Y"))
+        };
+        let [low, high] = self.bounds;
+        let dataset = Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .data(&self.points);
+        Chart::new(vec![dataset])
+            .x_axis(Axis::default().bounds(x).labels(vec![date(x[0]), date((x[0] + x[1]) / 2.0), date(x[1])]))
+            .y_axis(Axis::default().bounds(self.bounds).labels(
+                [low, (low + high) / 2.0, high].into_iter().map(|value| Span::raw(number(value))).collect()
+            ))
+    }
+
+    /// The last `width` values as sparkline bars, up to `BAR_MAX`. The lowest bound still gets a
+    /// bar, so it doesn't look like a missing value.
+    pub fn bars(&self, width: usize) -> Vec<u64> {
+        let [low, high] = self.bounds;
+        self.points[self.points.len().saturating_sub(width)..].iter()
+            .map(|(_, value)| 1 + ((value - low) / (high - low) * (BAR_MAX - 1) as f64).round().max(0.0) as u64)
+            .collect()
+    }
+}
+
+/// `value` without decimals when it's whole, and with one otherwise.
+pub fn number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}